@@ -0,0 +1,280 @@
+//! Product catalogue files used to seed the template pallet's genesis.
+//!
+//! Two formats are accepted, picked by the file extension:
+//!
+//! - JSON: `{ "manufacturers": [{ "account": "<ss58>", "metadata": "..." }],
+//!   "products": [{ "manufacturer": "<ss58>", "hash": "0x..." }] }`
+//! - CSV: one record per line, either `manufacturer,<ss58>,<metadata>` or
+//!   `product,<ss58>,<hash>`. Empty lines and lines starting with `#` are skipped.
+
+use frame_support::traits::Get;
+use node_template_runtime::{AccountId, Hash, MaxCidLength, MaxManufacturers};
+use serde::Deserialize;
+use sp_core::crypto::Ss58Codec;
+use std::{collections::BTreeSet, path::Path, str::FromStr};
+
+/// Manufacturers and products registered at genesis.
+#[derive(Debug, Clone, Default)]
+pub struct Catalogue {
+	/// Manufacturers and their metadata.
+	pub manufacturers: Vec<(AccountId, Vec<u8>)>,
+	/// Products and the manufacturer that produced them.
+	pub products: Vec<(AccountId, Hash)>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonCatalogue {
+	#[serde(default)]
+	manufacturers: Vec<JsonManufacturer>,
+	#[serde(default)]
+	products: Vec<JsonProduct>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonManufacturer {
+	account: String,
+	#[serde(default)]
+	metadata: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonProduct {
+	manufacturer: String,
+	hash: String,
+}
+
+impl Catalogue {
+	/// Read and validate a catalogue file, reporting every problem found in it.
+	pub fn from_file(path: &Path) -> Result<Self, String> {
+		let content = std::fs::read_to_string(path)
+			.map_err(|e| format!("Failed to read catalogue {}: {}", path.display(), e))?;
+
+		let is_csv = path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("csv"));
+		let (catalogue, mut errors) =
+			if is_csv { Self::parse_csv(&content) } else { Self::parse_json(&content)? };
+		errors.extend(catalogue.validate());
+
+		if errors.is_empty() {
+			Ok(catalogue)
+		} else {
+			Err(format!("Invalid catalogue {}:\n  {}", path.display(), errors.join("\n  ")))
+		}
+	}
+
+	fn parse_json(content: &str) -> Result<(Self, Vec<String>), String> {
+		let json: JsonCatalogue =
+			serde_json::from_str(content).map_err(|e| format!("Invalid catalogue JSON: {}", e))?;
+
+		let mut catalogue = Self::default();
+		let mut errors = Vec::new();
+		for (i, entry) in json.manufacturers.into_iter().enumerate() {
+			match parse_account(&entry.account) {
+				Ok(who) => catalogue.manufacturers.push((who, entry.metadata.into_bytes())),
+				Err(e) => errors.push(format!("manufacturers[{}]: {}", i, e)),
+			}
+		}
+		for (i, entry) in json.products.into_iter().enumerate() {
+			match (parse_account(&entry.manufacturer), parse_hash(&entry.hash)) {
+				(Ok(who), Ok(hash)) => catalogue.products.push((who, hash)),
+				(Err(e), _) | (_, Err(e)) => errors.push(format!("products[{}]: {}", i, e)),
+			}
+		}
+
+		Ok((catalogue, errors))
+	}
+
+	fn parse_csv(content: &str) -> (Self, Vec<String>) {
+		let mut catalogue = Self::default();
+		let mut errors = Vec::new();
+		for (i, line) in content.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue
+			}
+
+			let mut fields = line.splitn(3, ',').map(str::trim);
+			let record = match (fields.next(), fields.next(), fields.next()) {
+				(Some("manufacturer"), Some(account), metadata) => parse_account(account)
+					.map(|who| catalogue.manufacturers.push((who, metadata.unwrap_or_default().into()))),
+				(Some("product"), Some(account), Some(hash)) =>
+					parse_account(account).and_then(|who| {
+						parse_hash(hash).map(|hash| catalogue.products.push((who, hash)))
+					}),
+				_ => Err("expected `manufacturer,<account>,<metadata>` or `product,<account>,<hash>`"
+					.to_string()),
+			};
+			if let Err(e) = record {
+				errors.push(format!("line {}: {}", i + 1, e));
+			}
+		}
+
+		(catalogue, errors)
+	}
+
+	/// Duplicates, products of manufacturers that are not listed and entries over the runtime's
+	/// bounds, which would make the genesis build fail.
+	fn validate(&self) -> Vec<String> {
+		let mut errors = Vec::new();
+
+		if self.manufacturers.len() > MaxManufacturers::get() as usize {
+			errors.push(format!(
+				"{} manufacturers, at most {} can be registered",
+				self.manufacturers.len(),
+				MaxManufacturers::get()
+			));
+		}
+
+		let mut manufacturers = BTreeSet::new();
+		for (who, metadata) in self.manufacturers.iter() {
+			if !manufacturers.insert(who) {
+				errors.push(format!("duplicate manufacturer {}", who));
+			}
+			if metadata.len() > MaxCidLength::get() as usize {
+				errors.push(format!("metadata of {} is longer than {} bytes", who, MaxCidLength::get()));
+			}
+		}
+
+		let mut products = BTreeSet::new();
+		for (who, hash) in self.products.iter() {
+			if !manufacturers.contains(who) {
+				errors.push(format!("product {:?} of unknown manufacturer {}", hash, who));
+			}
+			if !products.insert(hash) {
+				errors.push(format!("duplicate product {:?}", hash));
+			}
+		}
+
+		errors
+	}
+}
+
+fn parse_account(account: &str) -> Result<AccountId, String> {
+	AccountId::from_ss58check(account).map_err(|e| format!("invalid account `{}`: {:?}", account, e))
+}
+
+fn parse_hash(hash: &str) -> Result<Hash, String> {
+	Hash::from_str(hash).map_err(|e| format!("invalid product hash `{}`: {}", hash, e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::AccountKeyring::{Alice, Bob};
+
+	fn catalogue_file(name: &str, content: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join(name);
+		std::fs::write(&path, content).unwrap();
+		(dir, path)
+	}
+
+	#[test]
+	fn reads_json_catalogue() {
+		let json = format!(
+			r#"{{
+				"manufacturers": [{{ "account": "{alice}", "metadata": "Qm1" }}, {{ "account": "{bob}" }}],
+				"products": [{{ "manufacturer": "{alice}", "hash": "0x{hash}" }}]
+			}}"#,
+			alice = Alice.to_account_id(),
+			bob = Bob.to_account_id(),
+			hash = "11".repeat(32),
+		);
+		let (_dir, path) = catalogue_file("catalogue.json", &json);
+
+		let catalogue = Catalogue::from_file(&path).unwrap();
+		assert_eq!(
+			catalogue.manufacturers,
+			vec![(Alice.to_account_id(), b"Qm1".to_vec()), (Bob.to_account_id(), Vec::new())]
+		);
+		assert_eq!(catalogue.products, vec![(Alice.to_account_id(), Hash::repeat_byte(0x11))]);
+	}
+
+	#[test]
+	fn reads_csv_catalogue() {
+		let csv = format!(
+			"# manufacturers first\nmanufacturer,{alice},Qm1, with a comma\n\nproduct, {alice} ,0x{hash}\n",
+			alice = Alice.to_account_id(),
+			hash = "22".repeat(32),
+		);
+		let (_dir, path) = catalogue_file("catalogue.CSV", &csv);
+
+		let catalogue = Catalogue::from_file(&path).unwrap();
+		assert_eq!(
+			catalogue.manufacturers,
+			vec![(Alice.to_account_id(), b"Qm1, with a comma".to_vec())]
+		);
+		assert_eq!(catalogue.products, vec![(Alice.to_account_id(), Hash::repeat_byte(0x22))]);
+	}
+
+	#[test]
+	fn reports_every_malformed_csv_line() {
+		let csv = format!(
+			"manufacturer,not-an-account\nproduct,{alice}\nproduct,{alice},0x1234\nrecall,{alice},0x00\n",
+			alice = Alice.to_account_id(),
+		);
+
+		let (catalogue, errors) = Catalogue::parse_csv(&csv);
+		assert!(catalogue.manufacturers.is_empty());
+		assert!(catalogue.products.is_empty());
+		assert_eq!(errors.len(), 4);
+		for (line, error) in errors.iter().enumerate() {
+			assert!(error.starts_with(&format!("line {}: ", line + 1)), "{}", error);
+		}
+	}
+
+	#[test]
+	fn rejects_unknown_json_fields_and_bad_entries() {
+		assert!(Catalogue::parse_json(r#"{ "manufacturer": [] }"#).is_err());
+
+		let json = format!(
+			r#"{{ "manufacturers": [{{ "account": "nobody" }}],
+				"products": [{{ "manufacturer": "{}", "hash": "0xzz" }}] }}"#,
+			Alice.to_account_id(),
+		);
+		let (_, errors) = Catalogue::parse_json(&json).unwrap();
+		assert_eq!(errors.len(), 2);
+		assert!(errors[0].starts_with("manufacturers[0]: invalid account"));
+		assert!(errors[1].starts_with("products[0]: invalid product hash"));
+	}
+
+	#[test]
+	fn validation_reports_inconsistent_catalogues() {
+		let catalogue = Catalogue {
+			manufacturers: vec![
+				(Alice.to_account_id(), Vec::new()),
+				(Alice.to_account_id(), vec![0; MaxCidLength::get() as usize + 1]),
+			],
+			products: vec![
+				(Alice.to_account_id(), Hash::repeat_byte(1)),
+				(Alice.to_account_id(), Hash::repeat_byte(1)),
+				(Bob.to_account_id(), Hash::repeat_byte(2)),
+			],
+		};
+
+		let errors = catalogue.validate();
+		assert_eq!(errors.len(), 4, "{:?}", errors);
+		assert!(errors[0].starts_with("duplicate manufacturer"));
+		assert!(errors[1].starts_with("metadata of"));
+		assert!(errors[2].starts_with("duplicate product"));
+		assert!(errors[3].starts_with("product") && errors[3].contains("unknown manufacturer"));
+	}
+
+	#[test]
+	fn invalid_file_is_rejected_with_all_errors() {
+		let csv = format!(
+			"manufacturer,{alice},\nmanufacturer,{alice},\nproduct,{bob},0x{hash}\nbogus\n",
+			alice = Alice.to_account_id(),
+			bob = Bob.to_account_id(),
+			hash = "33".repeat(32),
+		);
+		let (_dir, path) = catalogue_file("catalogue.csv", &csv);
+
+		let error = Catalogue::from_file(&path).unwrap_err();
+		assert!(error.contains("line 4: expected"), "{}", error);
+		assert!(error.contains("duplicate manufacturer"), "{}", error);
+		assert!(error.contains("unknown manufacturer"), "{}", error);
+	}
+}
@@ -1,12 +1,14 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Hash, Signature,
+	SudoConfig, SystemConfig, TemplateModuleConfig, WASM_BINARY,
 };
+use crate::catalogue::Catalogue;
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
-use sp_runtime::traits::{IdentifyAccount, Verify};
+use sp_runtime::traits::{BlakeTwo256, Hash as HashT, IdentifyAccount, Verify};
+use std::path::Path;
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -36,7 +38,29 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
 }
 
-pub fn development_config() -> Result<ChainSpec, String> {
+/// Generate a manufacturer account and its metadata from seed.
+pub fn manufacturer_from_seed(s: &str) -> (AccountId, Vec<u8>) {
+	(get_account_id_from_seed::<sr25519::Public>(s), format!("{} Manufacturing", s).into_bytes())
+}
+
+/// Alice and Bob as manufacturers, with three seed products each.
+pub fn dev_catalogue() -> Catalogue {
+	Catalogue {
+		manufacturers: vec![manufacturer_from_seed("Alice"), manufacturer_from_seed("Bob")],
+		products: [products_from_seed("Alice", 3), products_from_seed("Bob", 3)].concat(),
+	}
+}
+
+/// Generate `count` seed products of the manufacturer with the given seed.
+pub fn products_from_seed(s: &str, count: u32) -> Vec<(AccountId, Hash)> {
+	let who = get_account_id_from_seed::<sr25519::Public>(s);
+	(0..count)
+		.map(|i| (who.clone(), BlakeTwo256::hash(format!("{}/product/{}", s, i).as_bytes())))
+		.collect()
+}
+
+/// Development chain, seeded with `catalogue` instead of Alice's and Bob's products if given.
+pub fn development_config(catalogue: Option<Catalogue>) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -59,6 +83,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Manufacturers and seed products
+				catalogue.clone().unwrap_or_else(dev_catalogue),
 				true,
 			)
 		},
@@ -76,7 +102,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 	))
 }
 
-pub fn local_testnet_config() -> Result<ChainSpec, String> {
+/// Local testnet, seeded with `catalogue` instead of Alice's and Bob's products if given.
+pub fn local_testnet_config(catalogue: Option<Catalogue>) -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
 	Ok(ChainSpec::from_genesis(
@@ -107,6 +134,8 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Manufacturers and seed products
+				catalogue.clone().unwrap_or_else(dev_catalogue),
 				true,
 			)
 		},
@@ -124,12 +153,44 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// The chain spec in the JSON file at `path`, with `catalogue` as the template pallet's genesis.
+pub fn from_json_file_with_catalogue(
+	path: &Path,
+	catalogue: Catalogue,
+) -> Result<ChainSpec, String> {
+	let content = std::fs::read(path)
+		.map_err(|e| format!("Failed to read chain spec {}: {}", path.display(), e))?;
+	let mut spec: serde_json::Value = serde_json::from_slice(&content)
+		.map_err(|e| format!("Invalid chain spec {}: {}", path.display(), e))?;
+	seed_catalogue(&mut spec, catalogue)
+		.map_err(|e| format!("Failed to seed chain spec {}: {}", path.display(), e))?;
+
+	ChainSpec::from_json_bytes(serde_json::to_vec(&spec).map_err(|e| e.to_string())?)
+}
+
+// Replace the template pallet's genesis in the JSON of a chain spec with `catalogue`.
+fn seed_catalogue(spec: &mut serde_json::Value, catalogue: Catalogue) -> Result<(), String> {
+	let runtime = spec
+		.pointer_mut("/genesis/runtime")
+		.and_then(serde_json::Value::as_object_mut)
+		.ok_or("its genesis is raw, build the spec without `--raw` to seed it")?;
+	let template = serde_json::to_value(TemplateModuleConfig {
+		manufacturers: catalogue.manufacturers,
+		products: catalogue.products,
+	})
+	.map_err(|e| e.to_string())?;
+	runtime.insert("templateModule".into(), template);
+
+	Ok(())
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	catalogue: Catalogue,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -152,5 +213,42 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
+		template_module: TemplateModuleConfig {
+			manufacturers: catalogue.manufacturers,
+			products: catalogue.products,
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn catalogue_replaces_the_template_genesis() {
+		let mut spec = json!({
+			"name": "Production",
+			"genesis": { "runtime": {
+				"sudo": { "key": null },
+				"templateModule": { "manufacturers": [], "products": [] },
+			} },
+		});
+
+		seed_catalogue(&mut spec, dev_catalogue()).unwrap();
+
+		let template: TemplateModuleConfig =
+			serde_json::from_value(spec["genesis"]["runtime"]["templateModule"].clone()).unwrap();
+		assert_eq!(template.manufacturers, dev_catalogue().manufacturers);
+		assert_eq!(template.products, dev_catalogue().products);
+		assert_eq!(spec["genesis"]["runtime"]["sudo"], json!({ "key": null }));
+	}
+
+	#[test]
+	fn raw_chain_spec_is_not_seeded() {
+		let mut spec = json!({ "genesis": { "raw": { "top": {}, "childrenDefault": {} } } });
+
+		let error = seed_catalogue(&mut spec, dev_catalogue()).unwrap_err();
+		assert!(error.contains("--raw"), "{}", error);
 	}
 }
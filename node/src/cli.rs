@@ -1,4 +1,5 @@
 use sc_cli::RunCmd;
+use std::path::PathBuf;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +8,11 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Keep a timeline of every product's events in a local database, served by the
+	/// `authenticity_productHistory` RPC method.
+	#[arg(long)]
+	pub product_index: bool,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -16,7 +22,7 @@ pub enum Subcommand {
 	Key(sc_cli::KeySubcommand),
 
 	/// Build a chain specification.
-	BuildSpec(sc_cli::BuildSpecCmd),
+	BuildSpec(BuildSpecCmd),
 
 	/// Validate blocks.
 	CheckBlock(sc_cli::CheckBlockCmd),
@@ -50,4 +56,36 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Derive a product hash and the QR payload to print on the product.
+	Product(crate::product::ProductCmd),
+
+	/// Look a product up in the local database, without connecting to the network.
+	VerifyProduct(crate::inspect::VerifyProductCmd),
+
+	/// Export every product of the state at a block as CSV or JSON Lines.
+	ExportProducts(crate::inspect::ExportProductsCmd),
+}
+
+/// `build-spec` with an optional product catalogue to register at genesis.
+#[derive(Debug, clap::Parser)]
+pub struct BuildSpecCmd {
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub base: sc_cli::BuildSpecCmd,
+
+	/// Register the manufacturers and products of this JSON or CSV catalogue at genesis, instead
+	/// of the dev ones or those of the JSON chain spec passed to `--chain`.
+	#[arg(long, value_name = "FILE")]
+	pub catalogue: Option<PathBuf>,
+}
+
+impl sc_cli::CliConfiguration for BuildSpecCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		self.base.shared_params()
+	}
+
+	fn node_key_params(&self) -> Option<&sc_cli::NodeKeyParams> {
+		self.base.node_key_params()
+	}
 }
@@ -1,5 +1,6 @@
 use crate::{
 	benchmarking::{inherent_benchmark_data, RemarkBuilder, TransferKeepAliveBuilder},
+	catalogue::Catalogue,
 	chain_spec,
 	cli::{Cli, Subcommand},
 	service,
@@ -8,6 +9,7 @@ use frame_benchmarking_cli::{BenchmarkCmd, ExtrinsicFactory, SUBSTRATE_REFERENCE
 use node_template_runtime::{Block, EXISTENTIAL_DEPOSIT};
 use sc_cli::{ChainSpec, RuntimeVersion, SubstrateCli};
 use sc_service::PartialComponents;
+use sp_blockchain::HeaderBackend;
 use sp_keyring::Sr25519Keyring;
 
 #[cfg(feature = "try-runtime")]
@@ -39,11 +41,23 @@ impl SubstrateCli for Cli {
 	}
 
 	fn load_spec(&self, id: &str) -> Result<Box<dyn sc_service::ChainSpec>, String> {
+		let catalogue = match &self.subcommand {
+			Some(Subcommand::BuildSpec(cmd)) =>
+				cmd.catalogue.as_deref().map(Catalogue::from_file).transpose()?,
+			_ => None,
+		};
+
 		Ok(match id {
-			"dev" => Box::new(chain_spec::development_config()?),
-			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
-			path =>
-				Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
+			"dev" => Box::new(chain_spec::development_config(catalogue)?),
+			"" | "local" => Box::new(chain_spec::local_testnet_config(catalogue)?),
+			path => {
+				let path = std::path::PathBuf::from(path);
+				match catalogue {
+					Some(catalogue) =>
+						Box::new(chain_spec::from_json_file_with_catalogue(&path, catalogue)?),
+					None => Box::new(chain_spec::ChainSpec::from_json_file(path)?),
+				}
+			},
 		})
 	}
 
@@ -60,7 +74,7 @@ pub fn run() -> sc_cli::Result<()> {
 		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
 		Some(Subcommand::BuildSpec(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
-			runner.sync_run(|config| cmd.run(config.chain_spec, config.network))
+			runner.sync_run(|config| cmd.base.run(config.chain_spec, config.network))
 		},
 		Some(Subcommand::CheckBlock(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
@@ -206,10 +220,38 @@ pub fn run() -> sc_cli::Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| cmd.run::<Block>(&config))
 		},
+		Some(Subcommand::Product(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let genesis = match cmd.genesis {
+					Some(genesis) => genesis,
+					None => {
+						let PartialComponents { client, .. } = service::new_partial(&config)?;
+						client.info().genesis_hash
+					},
+				};
+				cmd.run(genesis)
+			})
+		},
+		Some(Subcommand::VerifyProduct(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(&client)
+			})
+		},
+		Some(Subcommand::ExportProducts(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(&client)
+			})
+		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let product_index = cli.product_index;
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				service::new_full(config, product_index).map_err(sc_cli::Error::Service)
 			})
 		},
 	}
@@ -0,0 +1,395 @@
+//! Per-product timeline of pallet-template events, indexed from finalized blocks.
+//!
+//! The index lives in its own parity-db database next to the chain database and only ever sees
+//! finalized blocks, so it never has to handle reorgs. Every event is stored under its own key,
+//! product hash first, so indexing a block never rewrites the timelines it extends.
+
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use node_template_runtime::{
+	opaque::Block, pallet_template, AccountId, BlockNumber, Hash, Runtime, RuntimeEvent,
+};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use sp_runtime::traits::Header;
+use std::{path::Path, sync::Arc};
+
+use crate::service::FullClient;
+
+const LOG_TARGET: &str = "product-index";
+
+/// Column holding the indexing progress.
+const META: u8 = 0;
+/// Ordered column holding the timeline entries, keyed by [`entry_key`].
+const TIMELINES: u8 = 1;
+/// Key of the number of the last indexed block.
+const LAST_INDEXED: &[u8] = b"last_indexed";
+
+// Key of the `index`th product event of block `number`. Big-endian numbers keep the entries of a
+// product in chain order.
+fn entry_key(hash: &Hash, number: BlockNumber, index: u32) -> Vec<u8> {
+	let mut key = hash.as_bytes().to_vec();
+	key.extend_from_slice(&number.to_be_bytes());
+	key.extend_from_slice(&index.to_be_bytes());
+	key
+}
+
+/// An event of a product's timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+	/// Block the event was emitted in.
+	pub block_number: BlockNumber,
+	/// Hash of that block.
+	pub block_hash: Hash,
+	/// Index of the extrinsic that emitted the event, `None` for block hooks.
+	pub extrinsic_index: Option<u32>,
+	/// Name of the pallet-template event, e.g. `AuthenticProduct`.
+	pub event: String,
+	/// Account that caused the event, if the event names one.
+	pub who: Option<AccountId>,
+}
+
+/// A pallet-template event about a single product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductEvent {
+	/// Product the event is about.
+	pub hash: Hash,
+	/// Name of the event.
+	pub name: &'static str,
+	/// Account that caused the event, if the event names one.
+	pub who: Option<AccountId>,
+}
+
+impl ProductEvent {
+	/// The product event carried by `event`, `None` for events that are not about a product.
+	pub fn from_event(event: &pallet_template::Event<Runtime>) -> Option<Self> {
+		use pallet_template::Event::*;
+
+		let (hash, name, who) = match event {
+			ProductAdded { hash, manufacturer } => (hash, "ProductAdded", Some(manufacturer)),
+			AuthenticProduct { hash, who } => (hash, "AuthenticProduct", Some(who)),
+			SaleFinalized { hash } => (hash, "SaleFinalized", None),
+			ProductReturned { hash, who } => (hash, "ProductReturned", Some(who)),
+			DisputeOpened { hash, who, .. } => (hash, "DisputeOpened", Some(who)),
+			DisputeResolved { hash, .. } => (hash, "DisputeResolved", None),
+			ProductCategorySet { hash, .. } => (hash, "ProductCategorySet", None),
+			WarrantyRegistered { hash, owner, .. } => (hash, "WarrantyRegistered", Some(owner)),
+			WarrantyClaimFiled { hash, owner } => (hash, "WarrantyClaimFiled", Some(owner)),
+			WarrantyClaimAccepted { hash } => (hash, "WarrantyClaimAccepted", None),
+			WarrantyClaimRejected { hash } => (hash, "WarrantyClaimRejected", None),
+			ProductVerified { hash, .. } => (hash, "ProductVerified", None),
+			_ => return None,
+		};
+
+		Some(ProductEvent { hash: *hash, name, who: who.cloned() })
+	}
+}
+
+fn read<T: Decode, C, B>(client: &C, at: Hash, key: Vec<u8>) -> Result<Option<T>, String>
+where
+	C: StorageProvider<Block, B>,
+	B: Backend<Block>,
+{
+	match client.storage(at, &StorageKey(key)).map_err(|e| e.to_string())? {
+		Some(data) => T::decode(&mut &data.0[..])
+			.map(Some)
+			.map_err(|e| format!("Failed to decode storage of block {:?}: {}", at, e)),
+		None => Ok(None),
+	}
+}
+
+/// Pallet-template events of block `at`, with the index of the extrinsic that emitted them.
+pub fn block_events<C, B>(
+	client: &C,
+	at: Hash,
+) -> Result<Vec<(Option<u32>, pallet_template::Event<Runtime>)>, String>
+where
+	C: StorageProvider<Block, B>,
+	B: Backend<Block>,
+{
+	let key = frame_system::Events::<Runtime>::hashed_key().to_vec();
+	let records = read(client, at, key)?.unwrap_or_default();
+	Ok(template_events(records))
+}
+
+// The pallet-template events of `records`.
+fn template_events(
+	records: Vec<frame_system::EventRecord<RuntimeEvent, Hash>>,
+) -> Vec<(Option<u32>, pallet_template::Event<Runtime>)> {
+	records
+		.into_iter()
+		.filter_map(|record| match record.event {
+			RuntimeEvent::TemplateModule(event) => {
+				let extrinsic_index = match record.phase {
+					frame_system::Phase::ApplyExtrinsic(index) => Some(index),
+					_ => None,
+				};
+				Some((extrinsic_index, event))
+			},
+			_ => None,
+		})
+		.collect()
+}
+
+/// Manufacturer of the product `hash` in the state of block `at`.
+pub fn product_manufacturer<C, B>(
+	client: &C,
+	at: Hash,
+	hash: Hash,
+) -> Result<Option<AccountId>, String>
+where
+	C: StorageProvider<Block, B>,
+	B: Backend<Block>,
+{
+	read(client, at, pallet_template::ProductManufacturer::<Runtime>::hashed_key_for(hash))
+}
+
+/// The product timelines database.
+pub struct ProductIndex {
+	db: parity_db::Db,
+}
+
+impl ProductIndex {
+	/// Open the index at `path`, creating it if needed.
+	pub fn open(path: &Path) -> Result<Self, String> {
+		let mut options = parity_db::Options::with_columns(path, 2);
+		options.columns[TIMELINES as usize].btree_index = true;
+		let db = parity_db::Db::open_or_create(&options)
+			.map_err(|e| format!("Failed to open product index at {}: {}", path.display(), e))?;
+		Ok(Self { db })
+	}
+
+	/// Timeline of `hash`, oldest event first.
+	pub fn timeline(&self, hash: &Hash) -> Result<Vec<TimelineEntry>, String> {
+		let mut iter = self.db.iter(TIMELINES).map_err(|e| e.to_string())?;
+		iter.seek(hash.as_bytes()).map_err(|e| e.to_string())?;
+
+		let mut timeline = Vec::new();
+		while let Some((key, value)) = iter.next().map_err(|e| e.to_string())? {
+			if !key.starts_with(hash.as_bytes()) {
+				break
+			}
+			timeline.push(TimelineEntry::decode(&mut &value[..]).map_err(|e| e.to_string())?);
+		}
+		Ok(timeline)
+	}
+
+	fn last_indexed(&self) -> Result<Option<BlockNumber>, String> {
+		match self.db.get(META, LAST_INDEXED).map_err(|e| e.to_string())? {
+			Some(value) =>
+				BlockNumber::decode(&mut &value[..]).map(Some).map_err(|e| e.to_string()),
+			None => Ok(None),
+		}
+	}
+
+	/// Index every finalized block that isn't indexed yet.
+	fn catch_up(&self, client: &FullClient) -> Result<(), String> {
+		let finalized = client.info().finalized_number;
+		let mut next = self.last_indexed()?.map_or(0, |last| last + 1);
+
+		while next <= finalized {
+			let hash = client
+				.hash(next)
+				.map_err(|e| e.to_string())?
+				.ok_or_else(|| format!("Finalized block #{} not found", next))?;
+			self.index_block(client, next, hash)?;
+			next += 1;
+		}
+
+		Ok(())
+	}
+
+	fn index_block(&self, client: &FullClient, number: BlockNumber, at: Hash) -> Result<(), String> {
+		self.store_block(number, at, block_events(client, at))
+	}
+
+	// Store the block's product events together with the progress marker, so a block is never
+	// indexed twice.
+	fn store_block(
+		&self,
+		number: BlockNumber,
+		at: Hash,
+		events: Result<Vec<(Option<u32>, pallet_template::Event<Runtime>)>, String>,
+	) -> Result<(), String> {
+		let events = events.unwrap_or_else(|e| {
+			// Blocks built by an older runtime may carry events this node can't decode.
+			log::warn!(target: LOG_TARGET, "Skipping block #{}: {}", number, e);
+			Vec::new()
+		});
+
+		let entries = events
+			.into_iter()
+			.filter_map(|(extrinsic_index, event)| {
+				ProductEvent::from_event(&event).map(|event| (extrinsic_index, event))
+			})
+			.enumerate()
+			.map(|(index, (extrinsic_index, event))| {
+				let entry = TimelineEntry {
+					block_number: number,
+					block_hash: at,
+					extrinsic_index,
+					event: event.name.into(),
+					who: event.who,
+				};
+				(TIMELINES, entry_key(&event.hash, number, index as u32), Some(entry.encode()))
+			});
+
+		let changes = entries.chain(Some((META, LAST_INDEXED.to_vec(), Some(number.encode()))));
+		self.db.commit(changes).map_err(|e| e.to_string())
+	}
+}
+
+/// Keep the index up to date with the finalized chain.
+///
+/// Catching up reads every block since the last run from the database, so spawn this as a
+/// blocking task.
+pub async fn run(client: Arc<FullClient>, index: Arc<ProductIndex>) {
+	let mut finality = client.finality_notification_stream();
+
+	if let Err(e) = index.catch_up(&client) {
+		log::error!(target: LOG_TARGET, "Failed to index finalized blocks: {}", e);
+	}
+
+	while let Some(notification) = finality.next().await {
+		if let Err(e) = index.catch_up(&client) {
+			log::error!(
+				target: LOG_TARGET,
+				"Failed to index up to block #{}: {}",
+				notification.header.number(),
+				e,
+			);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_system::{EventRecord, Phase};
+	use pallet_template::Event as TemplateEvent;
+	use sp_keyring::AccountKeyring::{Alice, Bob};
+
+	type Events = Vec<(Option<u32>, TemplateEvent<Runtime>)>;
+
+	fn record(phase: Phase, event: RuntimeEvent) -> EventRecord<RuntimeEvent, Hash> {
+		EventRecord { phase, event, topics: Vec::new() }
+	}
+
+	fn added(hash: Hash) -> TemplateEvent<Runtime> {
+		TemplateEvent::ProductAdded { hash, manufacturer: Alice.to_account_id() }
+	}
+
+	fn sold(hash: Hash) -> TemplateEvent<Runtime> {
+		TemplateEvent::AuthenticProduct { hash, who: Bob.to_account_id() }
+	}
+
+	fn entry(
+		number: BlockNumber,
+		extrinsic_index: Option<u32>,
+		event: &str,
+		who: Option<AccountId>,
+	) -> TimelineEntry {
+		TimelineEntry {
+			block_number: number,
+			block_hash: Hash::repeat_byte(number as u8),
+			extrinsic_index,
+			event: event.into(),
+			who,
+		}
+	}
+
+	fn store(index: &ProductIndex, number: BlockNumber, events: Result<Events, String>) {
+		index.store_block(number, Hash::repeat_byte(number as u8), events).unwrap();
+	}
+
+	#[test]
+	fn only_template_events_are_kept() {
+		let product = Hash::repeat_byte(1);
+		let success = frame_system::Event::ExtrinsicSuccess { dispatch_info: Default::default() };
+		let records = vec![
+			record(Phase::ApplyExtrinsic(1), RuntimeEvent::System(success)),
+			record(Phase::ApplyExtrinsic(1), RuntimeEvent::TemplateModule(sold(product))),
+			record(
+				Phase::Initialization,
+				RuntimeEvent::TemplateModule(TemplateEvent::SaleFinalized { hash: product }),
+			),
+		];
+
+		assert_eq!(
+			template_events(records),
+			vec![(Some(1), sold(product)), (None, TemplateEvent::SaleFinalized { hash: product })]
+		);
+	}
+
+	#[test]
+	fn product_events_are_named_after_the_pallet_event() {
+		let product = Hash::repeat_byte(1);
+		let expected =
+			ProductEvent { hash: product, name: "ProductAdded", who: Some(Alice.to_account_id()) };
+		assert_eq!(ProductEvent::from_event(&added(product)), Some(expected));
+
+		let manufacturer = TemplateEvent::ManufacturerAdded { who: Alice.to_account_id() };
+		assert_eq!(ProductEvent::from_event(&manufacturer), None);
+	}
+
+	#[test]
+	fn blocks_build_per_product_timelines() {
+		let dir = tempfile::tempdir().unwrap();
+		let index = ProductIndex::open(dir.path()).unwrap();
+		let (first, second) = (Hash::repeat_byte(1), Hash::repeat_byte(2));
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+
+		store(
+			&index,
+			1,
+			Ok(vec![
+				(Some(1), added(first)),
+				(Some(1), TemplateEvent::ManufacturerAdded { who: bob.clone() }),
+				(Some(2), added(second)),
+			]),
+		);
+		store(
+			&index,
+			2,
+			Ok(vec![(Some(1), sold(first)), (None, TemplateEvent::SaleFinalized { hash: first })]),
+		);
+
+		assert_eq!(
+			index.timeline(&first).unwrap(),
+			vec![
+				entry(1, Some(1), "ProductAdded", Some(alice.clone())),
+				entry(2, Some(1), "AuthenticProduct", Some(bob)),
+				entry(2, None, "SaleFinalized", None),
+			]
+		);
+		assert_eq!(
+			index.timeline(&second).unwrap(),
+			vec![entry(1, Some(2), "ProductAdded", Some(alice))]
+		);
+		assert_eq!(index.timeline(&Hash::repeat_byte(3)).unwrap(), Vec::new());
+		assert_eq!(index.last_indexed().unwrap(), Some(2));
+	}
+
+	#[test]
+	fn undecodable_blocks_are_skipped() {
+		let dir = tempfile::tempdir().unwrap();
+		let product = Hash::repeat_byte(1);
+
+		{
+			let index = ProductIndex::open(dir.path()).unwrap();
+			store(&index, 1, Ok(vec![(Some(1), added(product))]));
+			store(&index, 2, Err("Failed to decode storage of block".into()));
+		}
+
+		// The skipped block still counts as indexed, also after a restart.
+		let index = ProductIndex::open(dir.path()).unwrap();
+		assert_eq!(index.last_indexed().unwrap(), Some(2));
+		assert_eq!(
+			index.timeline(&product).unwrap(),
+			vec![entry(1, Some(1), "ProductAdded", Some(Alice.to_account_id()))]
+		);
+	}
+}
@@ -0,0 +1,359 @@
+//! Offline inspection of the template pallet's storage in the node's database.
+
+use crate::{product::QrPayload, service::FullClient};
+use codec::Decode;
+use frame_support::storage::StoragePrefixedMap;
+use node_template_runtime::{
+	opaque::Block,
+	pallet_template::{self, ProductStatus},
+	AccountId, BlockNumber, Hash, Moment, Runtime,
+};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::StorageProvider;
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, storage::StorageKey, twox_128};
+use std::{
+	collections::HashMap,
+	fs::File,
+	io::{self, BufWriter, Write},
+	path::PathBuf,
+	str::FromStr,
+};
+
+/// Where a product is in its lifecycle at a given block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductRecord {
+	/// Lifecycle status of the product.
+	pub status: ProductStatus,
+	/// Block the product entered its status at. Unknown until the sale is finalized.
+	pub since: Option<BlockNumber>,
+	/// Moment (ms since the Unix epoch) the return window closes at, for sales not finalized yet.
+	pub return_expires: Option<Moment>,
+	/// Manufacturer that registered the product.
+	pub manufacturer: Option<AccountId>,
+	/// Customer who bought the product.
+	pub owner: Option<AccountId>,
+}
+
+fn read<T: Decode>(client: &FullClient, at: Hash, key: Vec<u8>) -> sc_cli::Result<Option<T>> {
+	client
+		.storage(at, &StorageKey(key))?
+		.map(|data| T::decode(&mut &data.0[..]))
+		.transpose()
+		.map_err(|e| format!("Failed to decode pallet storage: {}", e).into())
+}
+
+/// Read the record of `hash` from the state at block `at`, `None` if it was never registered.
+pub fn product_record(
+	client: &FullClient,
+	at: Hash,
+	hash: Hash,
+) -> sc_cli::Result<Option<ProductRecord>> {
+	let return_expires: Option<Moment> =
+		read(client, at, pallet_template::PartialSellProduct::<Runtime>::hashed_key_for(hash))?;
+	let (status, since) = match return_expires {
+		// The return window is over, the sale is final even if nobody finalized it yet.
+		Some(expires) if Some(expires) <= now(client, at)? => (ProductStatus::Sold, None),
+		Some(_) => (ProductStatus::PendingReturn, None),
+		None => {
+			let sets = [
+				(ProductStatus::Registered, pallet_template::ProductsHash::<Runtime>::hashed_key_for(hash)),
+				(
+					ProductStatus::Returned,
+					pallet_template::ReturnedProducts::<Runtime>::hashed_key_for(hash),
+				),
+				(ProductStatus::Sold, pallet_template::SellProducts::<Runtime>::hashed_key_for(hash)),
+			];
+			let mut found = None;
+			for (status, key) in sets {
+				if let Some(since) = read::<BlockNumber>(client, at, key)? {
+					found = Some((status, Some(since)));
+					break
+				}
+			}
+			match found {
+				Some(found) => found,
+				None => return Ok(None),
+			}
+		},
+	};
+
+	Ok(Some(ProductRecord {
+		status,
+		since,
+		return_expires,
+		manufacturer: read(
+			client,
+			at,
+			pallet_template::ProductManufacturer::<Runtime>::hashed_key_for(hash),
+		)?,
+		owner: read(client, at, pallet_template::ProductOwner::<Runtime>::hashed_key_for(hash))?,
+	}))
+}
+
+/// Resolve `--at` to a block hash, the best block if not given.
+pub fn block_hash(client: &FullClient, at: &Option<BlockNumberOrHash>) -> sc_cli::Result<Hash> {
+	match at {
+		Some(at) => Ok(client.expect_block_hash_from_id(&at.parse::<Block>()?)?),
+		None => Ok(client.info().best_hash),
+	}
+}
+
+/// The `verify-product` command: look a product up in the local database, without networking.
+#[derive(Debug, clap::Parser)]
+pub struct VerifyProductCmd {
+	/// Product hash, or the QR payload printed on the product.
+	#[arg(value_name = "HASH_OR_PAYLOAD")]
+	pub product: String,
+
+	/// Block number or hash to read the state at. Defaults to the best block.
+	#[arg(long, value_name = "BLOCK")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl VerifyProductCmd {
+	/// Print the lifecycle status, manufacturer and expiry of the product.
+	pub fn run(&self, client: &FullClient) -> sc_cli::Result<()> {
+		let hash = match QrPayload::from_str(&self.product) {
+			Ok(payload) => {
+				if payload.genesis != client.info().genesis_hash {
+					return Err("Product was registered on another chain".into())
+				}
+				payload.hash
+			},
+			Err(_) => Hash::from_str(&self.product)
+				.map_err(|_| "Expected a product hash or a product QR payload")?,
+		};
+
+		let at = block_hash(client, &self.at)?;
+		let number = client.number(at)?.ok_or("Block not found")?;
+
+		println!("Product:      {:?}", hash);
+		println!("Block:        #{} ({:?})", number, at);
+
+		let record = match product_record(client, at, hash)? {
+			Some(record) => record,
+			None => {
+				println!("Status:       not registered, the product is not authentic");
+				return Ok(())
+			},
+		};
+
+		println!("Status:       {:?}", record.status);
+		if let Some(manufacturer) = &record.manufacturer {
+			println!("Manufacturer: {}", manufacturer.to_ss58check());
+		}
+		if let Some(owner) = &record.owner {
+			println!("Owner:        {}", owner.to_ss58check());
+		}
+		if let Some(expires) = record.return_expires {
+			println!("Expiry:       return window closes at {} ms since the Unix epoch", expires);
+		}
+		if let Some(since) = record.since {
+			println!("Since:        block #{}", since);
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for VerifyProductCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+/// Output format of `export-products`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+	/// Comma separated values with a header line.
+	Csv,
+	/// One JSON object per line.
+	Jsonl,
+}
+
+/// A product row of the export.
+#[derive(Serialize)]
+struct ProductRow {
+	hash: String,
+	status: String,
+	manufacturer: Option<String>,
+	owner: Option<String>,
+	/// Block the product entered its status at. Unknown for sales not finalized yet.
+	block: Option<BlockNumber>,
+	/// Timestamp of `block`, in milliseconds since the Unix epoch.
+	timestamp: Option<Moment>,
+	/// Moment the return window closes at, in milliseconds since the Unix epoch, for pending
+	/// sales.
+	return_expires: Option<Moment>,
+}
+
+impl ProductRow {
+	const CSV_HEADER: &'static str = "hash,status,manufacturer,owner,block,timestamp,return_expires";
+
+	fn to_csv(&self) -> String {
+		fn field<T: ToString>(value: &Option<T>) -> String {
+			value.as_ref().map(ToString::to_string).unwrap_or_default()
+		}
+		format!(
+			"{},{},{},{},{},{},{}",
+			self.hash,
+			self.status,
+			field(&self.manufacturer),
+			field(&self.owner),
+			field(&self.block),
+			field(&self.timestamp),
+			field(&self.return_expires),
+		)
+	}
+}
+
+/// The `export-products` command: write every product of the state at a block, for audits.
+#[derive(Debug, clap::Parser)]
+pub struct ExportProductsCmd {
+	/// Output file. Defaults to stdout.
+	#[arg(long, short, value_name = "FILE")]
+	pub output: Option<PathBuf>,
+
+	/// Output format.
+	#[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+	pub format: ExportFormat,
+
+	/// Block number or hash to read the state at. Defaults to the best block.
+	#[arg(long, value_name = "BLOCK")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ExportProductsCmd {
+	/// Walk the lifecycle sets and write one row per product.
+	pub fn run(&self, client: &FullClient) -> sc_cli::Result<()> {
+		let at = block_hash(client, &self.at)?;
+		let mut out: Box<dyn Write> = match &self.output {
+			Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+			None => Box::new(BufWriter::new(io::stdout())),
+		};
+		if self.format == ExportFormat::Csv {
+			writeln!(out, "{}", ProductRow::CSV_HEADER)?;
+		}
+
+		let sets = [
+			(ProductStatus::Registered, pallet_template::ProductsHash::<Runtime>::final_prefix()),
+			(ProductStatus::PendingReturn, pallet_template::PartialSellProduct::<Runtime>::final_prefix()),
+			(ProductStatus::Returned, pallet_template::ReturnedProducts::<Runtime>::final_prefix()),
+			(ProductStatus::Sold, pallet_template::SellProducts::<Runtime>::final_prefix()),
+		];
+		let mut timestamps = HashMap::new();
+		let mut count = 0usize;
+		let now = now(client, at)?;
+
+		for (status, prefix) in sets {
+			let prefix = StorageKey(prefix.to_vec());
+			for (key, value) in client.storage_pairs(at, &prefix)? {
+				// Keys are `Blake2_128Concat`: the 16 byte hash precedes the encoded product hash.
+				let hash = Hash::decode(&mut &key.0[prefix.0.len() + 16..])
+					.map_err(|e| format!("Invalid product key: {}", e))?;
+				// Pending sales hold their deadline, the other sets the block the status started.
+				let (status, block, return_expires) = match status {
+					ProductStatus::PendingReturn => {
+						let expires = decode::<Moment>(&value.0)?;
+						// Sales whose window is over are final before anyone finalizes them.
+						let status =
+							if Some(expires) <= now { ProductStatus::Sold } else { ProductStatus::PendingReturn };
+						(status, None, Some(expires))
+					},
+					_ => (status, Some(decode::<BlockNumber>(&value.0)?), None),
+				};
+				let timestamp = match block {
+					Some(block) => match timestamps.get(&block) {
+						Some(timestamp) => *timestamp,
+						None => {
+							let timestamp = timestamp_at(client, block)?;
+							timestamps.insert(block, timestamp);
+							timestamp
+						},
+					},
+					None => None,
+				};
+
+				let manufacturer: Option<AccountId> = read(
+					client,
+					at,
+					pallet_template::ProductManufacturer::<Runtime>::hashed_key_for(hash),
+				)?;
+				let owner: Option<AccountId> =
+					read(client, at, pallet_template::ProductOwner::<Runtime>::hashed_key_for(hash))?;
+
+				let row = ProductRow {
+					hash: format!("{:?}", hash),
+					status: format!("{:?}", status),
+					manufacturer: manufacturer.map(|who| who.to_ss58check()),
+					owner: owner.map(|who| who.to_ss58check()),
+					block,
+					timestamp,
+					return_expires,
+				};
+				match self.format {
+					ExportFormat::Csv => writeln!(out, "{}", row.to_csv())?,
+					ExportFormat::Jsonl => writeln!(
+						out,
+						"{}",
+						serde_json::to_string(&row).map_err(|e| format!("Failed to encode row: {}", e))?
+					)?,
+				}
+				count += 1;
+			}
+		}
+
+		out.flush()?;
+		eprintln!("Exported {} products at block {:?}", count, at);
+		Ok(())
+	}
+}
+
+fn decode<T: Decode>(value: &[u8]) -> sc_cli::Result<T> {
+	T::decode(&mut &value[..]).map_err(|e| format!("Invalid product entry: {}", e).into())
+}
+
+/// `pallet_timestamp::Now` at the end of block `number`, if the block is known.
+fn timestamp_at(client: &FullClient, number: BlockNumber) -> sc_cli::Result<Option<Moment>> {
+	match client.hash(number)? {
+		Some(hash) => now(client, hash),
+		None => Ok(None),
+	}
+}
+
+// Timestamp of block `at`.
+fn now(client: &FullClient, at: Hash) -> sc_cli::Result<Option<Moment>> {
+	read(client, at, [twox_128(b"Timestamp"), twox_128(b"Now")].concat())
+}
+
+impl CliConfiguration for ExportProductsCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -1,12 +1,16 @@
 //! Substrate Node Template CLI library.
 #![warn(missing_docs)]
 
+mod catalogue;
 mod chain_spec;
 #[macro_use]
 mod service;
 mod benchmarking;
 mod cli;
 mod command;
+mod indexer;
+mod inspect;
+mod product;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
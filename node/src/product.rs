@@ -0,0 +1,214 @@
+//! Canonical product hashes and the QR payloads printed on products.
+
+use node_template_runtime::{AccountId, Hash, Runtime};
+use qrcode::{render::svg, QrCode};
+use sc_cli::{CliConfiguration, SharedParams};
+use sp_core::crypto::Ss58Codec;
+use sp_runtime::traits::Hash as HashT;
+use std::{fmt, path::PathBuf, str::FromStr};
+
+/// The runtime's hashing, so product hashes match what the pallet stores.
+type Hashing = <Runtime as frame_system::Config>::Hashing;
+
+/// Domain separator of the product hash derivation.
+const HASH_DOMAIN: &[u8] = b"authenticity/product/v1";
+
+/// Prefix and version of the QR payload format.
+const PAYLOAD_PREFIX: &str = "auth";
+const PAYLOAD_VERSION: &str = "v1";
+
+/// Derive the canonical hash of a product.
+///
+/// Without the manufacturer's secret nobody can derive the hash of a product from the details
+/// printed on its packaging.
+pub fn product_hash(manufacturer: &AccountId, secret: &str, sku: &str, lot: &str, serial: &str) -> Hash {
+	Hashing::hash_of(&(HASH_DOMAIN, manufacturer, secret, sku, lot, serial))
+}
+
+/// Content of a product's QR code: `auth:v1:<product hash>:<genesis hash>:<manufacturer SS58>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrPayload {
+	/// Hash the product is registered under.
+	pub hash: Hash,
+	/// Genesis hash of the chain the product is registered on.
+	pub genesis: Hash,
+	/// Manufacturer that registered the product.
+	pub manufacturer: AccountId,
+}
+
+impl fmt::Display for QrPayload {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"{}:{}:{:?}:{:?}:{}",
+			PAYLOAD_PREFIX,
+			PAYLOAD_VERSION,
+			self.hash,
+			self.genesis,
+			self.manufacturer.to_ss58check(),
+		)
+	}
+}
+
+impl FromStr for QrPayload {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let fields: Vec<&str> = s.trim().split(':').collect();
+		match fields.as_slice() {
+			[PAYLOAD_PREFIX, PAYLOAD_VERSION, hash, genesis, manufacturer] => Ok(QrPayload {
+				hash: Hash::from_str(hash).map_err(|e| format!("Invalid product hash: {}", e))?,
+				genesis: Hash::from_str(genesis).map_err(|e| format!("Invalid genesis hash: {}", e))?,
+				manufacturer: AccountId::from_ss58check(manufacturer)
+					.map_err(|e| format!("Invalid manufacturer: {:?}", e))?,
+			}),
+			[PAYLOAD_PREFIX, version, ..] => Err(format!("Unsupported QR payload version {}", version)),
+			_ => Err("Not a product QR payload".into()),
+		}
+	}
+}
+
+/// The `product` command: derive a product's hash and its QR payload.
+#[derive(Debug, clap::Parser)]
+pub struct ProductCmd {
+	/// Stock keeping unit of the product.
+	#[arg(long)]
+	pub sku: String,
+
+	/// Production lot of the product.
+	#[arg(long)]
+	pub lot: String,
+
+	/// Serial number of the product.
+	#[arg(long)]
+	pub serial: String,
+
+	/// SS58 address of the manufacturer registering the product.
+	#[arg(long, value_parser = parse_account)]
+	pub manufacturer: AccountId,
+
+	/// Manufacturer secret mixed into the hash. Prefer passing it through the environment.
+	#[arg(long, env = "PRODUCT_SECRET", hide_env_values = true)]
+	pub secret: String,
+
+	/// Genesis hash of the chain the product is registered on.
+	///
+	/// Read from the node's database for the `--chain` if not given.
+	#[arg(long)]
+	pub genesis: Option<Hash>,
+
+	/// Write the QR code to this file, as SVG if it ends in `.svg` and PNG otherwise.
+	#[arg(long, value_name = "FILE")]
+	pub qr: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+fn parse_account(account: &str) -> Result<AccountId, String> {
+	AccountId::from_ss58check(account).map_err(|e| format!("Invalid SS58 address: {:?}", e))
+}
+
+impl ProductCmd {
+	/// Print the product hash and QR payload, and write the QR image if asked to.
+	pub fn run(&self, genesis: Hash) -> sc_cli::Result<()> {
+		let hash = product_hash(&self.manufacturer, &self.secret, &self.sku, &self.lot, &self.serial);
+		let payload = QrPayload { hash, genesis, manufacturer: self.manufacturer.clone() }.to_string();
+
+		println!("Product hash: {:?}", hash);
+		println!("QR payload:   {}", payload);
+
+		if let Some(path) = &self.qr {
+			let code = QrCode::new(payload.as_bytes())
+				.map_err(|e| format!("Failed to encode QR code: {}", e))?;
+			let is_svg = path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("svg"));
+			if is_svg {
+				let image = code.render::<svg::Color>().min_dimensions(256, 256).build();
+				std::fs::write(path, image)?;
+			} else {
+				code.render::<image::Luma<u8>>()
+					.min_dimensions(256, 256)
+					.build()
+					.save(path)
+					.map_err(|e| format!("Failed to write QR code: {}", e))?;
+			}
+			println!("QR code:      {}", path.display());
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ProductCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::AccountKeyring::{Alice, Bob};
+
+	fn payload() -> QrPayload {
+		QrPayload {
+			hash: product_hash(&Alice.to_account_id(), "secret", "SKU-1", "LOT-7", "0001"),
+			genesis: Hash::repeat_byte(0xaa),
+			manufacturer: Alice.to_account_id(),
+		}
+	}
+
+	#[test]
+	fn product_hash_depends_on_every_detail() {
+		let alice = Alice.to_account_id();
+		let hash = product_hash(&alice, "secret", "SKU-1", "LOT-7", "0001");
+		assert_eq!(hash, product_hash(&alice, "secret", "SKU-1", "LOT-7", "0001"));
+
+		assert_ne!(hash, product_hash(&Bob.to_account_id(), "secret", "SKU-1", "LOT-7", "0001"));
+		assert_ne!(hash, product_hash(&alice, "other", "SKU-1", "LOT-7", "0001"));
+		assert_ne!(hash, product_hash(&alice, "secret", "SKU-2", "LOT-7", "0001"));
+		assert_ne!(hash, product_hash(&alice, "secret", "SKU-1", "LOT-8", "0001"));
+		assert_ne!(hash, product_hash(&alice, "secret", "SKU-1", "LOT-7", "0002"));
+		// Fields are length-prefixed, so moving characters between them changes the hash.
+		assert_ne!(hash, product_hash(&alice, "secret", "SKU-1L", "OT-7", "0001"));
+	}
+
+	#[test]
+	fn qr_payload_round_trips() {
+		let payload = payload();
+		let encoded = payload.to_string();
+		assert!(encoded.starts_with("auth:v1:0x"));
+		assert!(encoded.ends_with(&Alice.to_account_id().to_ss58check()));
+
+		assert_eq!(encoded.parse::<QrPayload>(), Ok(payload.clone()));
+		// Scanners may add surrounding whitespace.
+		assert_eq!(format!(" {}\n", encoded).parse::<QrPayload>(), Ok(payload));
+	}
+
+	#[test]
+	fn tampered_qr_payload_is_rejected() {
+		let encoded = payload().to_string();
+		let fields: Vec<&str> = encoded.split(':').collect();
+		let with = |index: usize, value: &str| {
+			let mut fields = fields.clone();
+			fields[index] = value;
+			fields.join(":")
+		};
+
+		let error = |payload: String| payload.parse::<QrPayload>().unwrap_err();
+
+		// A changed manufacturer address fails the SS58 checksum.
+		let mut manufacturer = fields[4].to_string();
+		let replacement = if manufacturer.as_bytes()[10] == b'2' { "3" } else { "2" };
+		manufacturer.replace_range(10..11, replacement);
+		assert!(error(with(4, &manufacturer)).starts_with("Invalid manufacturer"));
+
+		assert!(error(with(2, &fields[2][..20])).starts_with("Invalid product hash"));
+		assert!(error(with(3, "0xgenesis")).starts_with("Invalid genesis hash"));
+		assert_eq!(error(with(1, "v2")), "Unsupported QR payload version v2");
+		assert_eq!(error(with(0, "ath")), "Not a product QR payload");
+		assert!(format!("{}:extra", encoded).parse::<QrPayload>().is_err());
+		assert_eq!(error(String::new()), "Not a product QR payload");
+	}
+}
@@ -0,0 +1,275 @@
+//! Authenticity RPC methods served by the node itself rather than the runtime.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use futures::{future, stream, FutureExt, Stream, StreamExt};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::{
+		error::{CallError, ErrorObject},
+		SubscriptionResult,
+	},
+	SubscriptionSink,
+};
+use node_template_runtime::{opaque::Block, AccountId, BlockNumber, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+
+use crate::indexer::{self, ProductEvent, ProductIndex, TimelineEntry};
+
+const LOG_TARGET: &str = "authenticity-rpc";
+
+/// Error code returned when the node runs without `--product-index`.
+const INDEX_DISABLED: i32 = 1;
+/// Error code returned when the product index can't be read.
+const INDEX_ERROR: i32 = 2;
+
+/// A product event pushed to subscribers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductUpdate {
+	/// Product the event is about.
+	pub hash: Hash,
+	/// Name of the pallet-template event, e.g. `AuthenticProduct`.
+	pub event: String,
+	/// Account that caused the event, if the event names one.
+	pub who: Option<AccountId>,
+	/// Manufacturer that registered the product.
+	pub manufacturer: Option<AccountId>,
+	/// Block the event was emitted in.
+	pub block_number: BlockNumber,
+	/// Hash of that block.
+	pub block_hash: Hash,
+	/// Whether the block is finalized. Every event is pushed once when its block is imported and
+	/// again when the block is finalized; events of blocks that are later retracted are never
+	/// pushed as finalized.
+	pub finalized: bool,
+}
+
+impl ProductUpdate {
+	/// Whether the update is about the product `hash`.
+	fn is_about(&self, hash: &Hash) -> bool {
+		self.hash == *hash
+	}
+
+	/// Whether the update is about a product `manufacturer` registered.
+	fn is_from(&self, manufacturer: &AccountId) -> bool {
+		self.manufacturer.as_ref() == Some(manufacturer)
+	}
+}
+
+/// Authenticity RPC methods.
+#[rpc(server)]
+pub trait AuthenticityApi {
+	/// Lifecycle events of a product, oldest first, as recorded by the node's product index.
+	#[method(name = "authenticity_productHistory")]
+	fn product_history(&self, hash: Hash) -> RpcResult<Vec<TimelineEntry>>;
+
+	/// Events of a product as its blocks are imported and finalized.
+	#[subscription(
+		name = "authenticity_subscribeProduct" => "authenticity_product",
+		unsubscribe = "authenticity_unsubscribeProduct",
+		item = ProductUpdate,
+	)]
+	fn subscribe_product(&self, hash: Hash);
+
+	/// Events of every product of a manufacturer as their blocks are imported and finalized.
+	#[subscription(
+		name = "authenticity_subscribeManufacturer" => "authenticity_manufacturer",
+		unsubscribe = "authenticity_unsubscribeManufacturer",
+		item = ProductUpdate,
+	)]
+	fn subscribe_manufacturer(&self, manufacturer: AccountId);
+}
+
+/// Implementation of [`AuthenticityApiServer`].
+pub struct Authenticity<C, B> {
+	client: Arc<C>,
+	executor: SubscriptionTaskExecutor,
+	index: Option<Arc<ProductIndex>>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Authenticity<C, B> {
+	/// Serve the timelines of `index`, if the node keeps one, and subscriptions to `client`'s
+	/// blocks.
+	pub fn new(
+		client: Arc<C>,
+		executor: SubscriptionTaskExecutor,
+		index: Option<Arc<ProductIndex>>,
+	) -> Self {
+		Self { client, executor, index, _marker: Default::default() }
+	}
+}
+
+impl<C, B> Authenticity<C, B>
+where
+	C: BlockchainEvents<Block> + StorageProvider<Block, B> + HeaderBackend<Block> + 'static,
+	C: Send + Sync,
+	B: Backend<Block> + 'static,
+{
+	/// Product events of every imported and every finalized block.
+	fn updates(&self) -> impl Stream<Item = ProductUpdate> + Send + 'static {
+		let imported = self
+			.client
+			.import_notification_stream()
+			.map(|notification| vec![(notification.hash, false)]);
+		// A finality notification covers the blocks finalized implicitly along with its block.
+		let finalized = self.client.finality_notification_stream().map(|notification| {
+			notification
+				.tree_route
+				.iter()
+				.copied()
+				.chain(Some(notification.hash))
+				.map(|hash| (hash, true))
+				.collect::<Vec<_>>()
+		});
+
+		let client = self.client.clone();
+		stream::select(imported, finalized).flat_map(move |blocks| {
+			let updates: Vec<_> = blocks
+				.into_iter()
+				.flat_map(|(hash, finalized)| block_updates(&*client, hash, finalized))
+				.collect();
+			stream::iter(updates)
+		})
+	}
+
+	fn subscribe(
+		&self,
+		mut sink: SubscriptionSink,
+		filter: impl Fn(&ProductUpdate) -> bool + Send + 'static,
+	) {
+		let updates = self.updates().filter(move |update| future::ready(filter(update)));
+		let fut = async move {
+			sink.pipe_from_stream(updates).await;
+		};
+		self.executor.spawn("authenticity-rpc-subscription", Some("rpc"), fut.boxed());
+	}
+}
+
+/// Product events of block `at`.
+fn block_updates<C, B>(client: &C, at: Hash, finalized: bool) -> Vec<ProductUpdate>
+where
+	C: StorageProvider<Block, B> + HeaderBackend<Block>,
+	B: Backend<Block>,
+{
+	let result = client.number(at).map_err(|e| e.to_string()).and_then(|number| {
+		let number = number.ok_or_else(|| format!("Block {:?} not found", at))?;
+		let mut updates = Vec::new();
+		for (_, event) in indexer::block_events(client, at)? {
+			let event = match ProductEvent::from_event(&event) {
+				Some(event) => event,
+				None => continue,
+			};
+			updates.push(ProductUpdate {
+				hash: event.hash,
+				event: event.name.into(),
+				who: event.who,
+				manufacturer: indexer::product_manufacturer(client, at, event.hash)?,
+				block_number: number,
+				block_hash: at,
+				finalized,
+			});
+		}
+		Ok(updates)
+	});
+
+	result.unwrap_or_else(|e: String| {
+		log::debug!(target: LOG_TARGET, "Skipping events of block {:?}: {}", at, e);
+		Vec::new()
+	})
+}
+
+fn error(code: i32, message: &str, data: Option<String>) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(code, message, data)).into()
+}
+
+/// Timeline of `hash` in `index`, if the node keeps one.
+fn history(index: Option<&ProductIndex>, hash: Hash) -> RpcResult<Vec<TimelineEntry>> {
+	let index = index.ok_or_else(|| {
+		error(INDEX_DISABLED, "Product index is disabled, start the node with --product-index", None)
+	})?;
+	index
+		.timeline(&hash)
+		.map_err(|e| error(INDEX_ERROR, "Failed to read the product index", Some(e)))
+}
+
+impl<C, B> AuthenticityApiServer for Authenticity<C, B>
+where
+	C: BlockchainEvents<Block> + StorageProvider<Block, B> + HeaderBackend<Block> + 'static,
+	C: Send + Sync,
+	B: Backend<Block> + 'static,
+{
+	fn product_history(&self, hash: Hash) -> RpcResult<Vec<TimelineEntry>> {
+		history(self.index.as_deref(), hash)
+	}
+
+	fn subscribe_product(&self, sink: SubscriptionSink, hash: Hash) -> SubscriptionResult {
+		self.subscribe(sink, move |update| update.is_about(&hash));
+		Ok(())
+	}
+
+	fn subscribe_manufacturer(
+		&self,
+		sink: SubscriptionSink,
+		manufacturer: AccountId,
+	) -> SubscriptionResult {
+		self.subscribe(sink, move |update| update.is_from(&manufacturer));
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpsee::core::Error;
+	use sp_keyring::AccountKeyring::{Alice, Bob};
+
+	fn update(hash: Hash, manufacturer: Option<AccountId>) -> ProductUpdate {
+		ProductUpdate {
+			hash,
+			event: "AuthenticProduct".into(),
+			who: Some(Bob.to_account_id()),
+			manufacturer,
+			block_number: 1,
+			block_hash: Hash::repeat_byte(0xbb),
+			finalized: false,
+		}
+	}
+
+	fn code(error: Error) -> i32 {
+		match error {
+			Error::Call(CallError::Custom(error)) => error.code(),
+			other => panic!("Unexpected error {:?}", other),
+		}
+	}
+
+	#[test]
+	fn product_filter_matches_the_product_only() {
+		let product = Hash::repeat_byte(1);
+		assert!(update(product, None).is_about(&product));
+		assert!(!update(Hash::repeat_byte(2), None).is_about(&product));
+	}
+
+	#[test]
+	fn manufacturer_filter_matches_registered_products_only() {
+		let alice = Alice.to_account_id();
+		assert!(update(Hash::repeat_byte(1), Some(alice.clone())).is_from(&alice));
+		assert!(!update(Hash::repeat_byte(1), Some(Bob.to_account_id())).is_from(&alice));
+		// The buyer isn't the manufacturer, and products without one never match.
+		assert!(!update(Hash::repeat_byte(1), None).is_from(&Bob.to_account_id()));
+	}
+
+	#[test]
+	fn history_needs_the_product_index() {
+		assert_eq!(code(history(None, Hash::repeat_byte(1)).unwrap_err()), INDEX_DISABLED);
+
+		let dir = tempfile::tempdir().unwrap();
+		let index = ProductIndex::open(dir.path()).unwrap();
+		assert_eq!(history(Some(&index), Hash::repeat_byte(1)).unwrap(), Vec::new());
+	}
+}
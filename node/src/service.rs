@@ -1,5 +1,6 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use crate::indexer::ProductIndex;
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_client_api::BlockBackend;
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
@@ -155,7 +156,9 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+///
+/// With `product_index` the node keeps a timeline of every product's events, see [`ProductIndex`].
+pub fn new_full(mut config: Configuration, product_index: bool) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -204,6 +207,16 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		})?;
 
 	if config.offchain_worker.enabled {
+		// On dev chains Alice is a manufacturer, let the offchain worker sync her catalogue.
+		if config.chain_spec.chain_type() == sc_service::ChainType::Development {
+			sp_keystore::SyncCryptoStore::sr25519_generate_new(
+				&*keystore_container.sync_keystore(),
+				pallet_template::KEY_TYPE,
+				Some("//Alice"),
+			)
+			.map_err(|e| ServiceError::Other(format!("Failed to insert catalogue key: {}", e)))?;
+		}
+
 		sc_service::build_offchain_workers(
 			&config,
 			task_manager.spawn_handle(),
@@ -212,6 +225,24 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		);
 	}
 
+	let product_index = if product_index {
+		let path = config
+			.base_path
+			.as_ref()
+			.ok_or_else(|| ServiceError::Other("The product index needs a base path".into()))?
+			.config_dir(config.chain_spec.id())
+			.join("product_timelines");
+		let index = Arc::new(ProductIndex::open(&path).map_err(ServiceError::Other)?);
+		task_manager.spawn_handle().spawn_blocking(
+			"product-index",
+			None,
+			crate::indexer::run(client.clone(), index.clone()),
+		);
+		Some(index)
+	} else {
+		None
+	};
+
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks: Option<()> = None;
@@ -223,9 +254,14 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				subscription_executor,
+				product_index: product_index.clone(),
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
@@ -0,0 +1,62 @@
+//! Runtime API definition for pallet-template.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_template::{
+	CategoryId, ProductCounts, ProductPage, ProductStatus, RangeCursor, Warranty,
+};
+
+sp_api::decl_runtime_apis! {
+	/// Queries of the product lifecycle.
+	pub trait TemplateApi<AccountId, Hash, BlockNumber, Moment>
+	where
+		AccountId: Codec,
+		Hash: Codec,
+		BlockNumber: Codec,
+		Moment: Codec,
+	{
+		/// Lifecycle status of a product, `None` if it was never registered.
+		fn product_status(hash: Hash) -> Option<ProductStatus>;
+
+		/// Category the product belongs to.
+		fn product_category(hash: Hash) -> CategoryId;
+
+		/// Warranty length of a category in milliseconds, `None` if the category has no warranty.
+		fn warranty_period(category: CategoryId) -> Option<Moment>;
+
+		/// Warranty registered for a product, if any. It expires at a moment in milliseconds.
+		fn warranty(hash: Hash) -> Option<Warranty<AccountId, Moment>>;
+
+		/// Products with `status`, starting after `cursor`.
+		///
+		/// A page holds at most `limit` products and never more than the pallet's `MaxPageSize`.
+		/// It can be shorter while `next` is `Some`; keep passing `next` back until it is `None`.
+		fn products_by_status(
+			status: ProductStatus,
+			cursor: Option<Hash>,
+			limit: u32,
+		) -> ProductPage<Hash, Hash>;
+
+		/// Products registered by `manufacturer`, starting after `cursor`. Pages as
+		/// `products_by_status`.
+		fn products_by_manufacturer(
+			manufacturer: AccountId,
+			cursor: Option<Hash>,
+			limit: u32,
+		) -> ProductPage<Hash, Hash>;
+
+		/// Products registered between blocks `from` and `to` inclusive, ordered by block and
+		/// starting after `cursor`. Pages as `products_by_status`.
+		fn products_registered_between(
+			from: BlockNumber,
+			to: BlockNumber,
+			cursor: Option<RangeCursor<BlockNumber, Hash>>,
+			limit: u32,
+		) -> ProductPage<Hash, RangeCursor<BlockNumber, Hash>>;
+
+		/// Number of the manufacturer's products in each lifecycle status. Sales whose return
+		/// window is over count as sold, like in `product_status`.
+		fn manufacturer_counts(manufacturer: AccountId) -> ProductCounts;
+	}
+}
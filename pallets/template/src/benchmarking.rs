@@ -4,16 +4,365 @@ use super::*;
 
 #[allow(unused)]
 use crate::Pallet as Template;
-use frame_benchmarking::v1::{benchmarks, whitelisted_caller};
-use frame_system::RawOrigin;
+use frame_benchmarking::v1::{
+	account, benchmarks, whitelisted_caller, BenchmarkError, BenchmarkResult,
+};
+use frame_support::{
+	traits::{Currency, EnsureOrigin, Hooks, ReservableCurrency},
+	weights::Weight,
+	BoundedVec,
+};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use sp_runtime::traits::{Hash, Zero};
+use sp_std::{boxed::Box, vec::Vec};
+
+fn product<T: Config>(i: u32) -> T::Hash {
+	T::Hashing::hash_of(&i)
+}
+
+// A manufacturer able to reserve the finalization deposits of `sales` sales.
+fn seller<T: Config>(sales: u32) -> T::AccountId {
+	let who: T::AccountId = account("manufacturer", 0, 0);
+	let deposits = T::FinalizeDeposit::get().saturating_mul(sales.into());
+	T::Currency::make_free_balance_be(&who, T::Currency::minimum_balance().saturating_add(deposits));
+	who
+}
+
+fn manufacturer<T: Config>() -> T::AccountId {
+	let caller: T::AccountId = whitelisted_caller();
+	Manufacturer::<T>::put(BoundedVec::truncate_from(sp_std::vec![caller.clone()]));
+	caller
+}
+
+// A product of `maker` whose owner has a warranty claim pending.
+fn claimed_warranty<T: Config>(maker: &T::AccountId) -> T::Hash {
+	let hash = product::<T>(0);
+	ProductManufacturer::<T>::insert(&hash, maker);
+	Warranties::<T>::insert(&hash, Warranty {
+		owner: account("customer", 0, 0),
+		expires: 1_000u32.into(),
+		status: WarrantyStatus::Claimed,
+		claim_reason: Some(BoundedVec::truncate_from(sp_std::vec![0; T::MaxCidLength::get() as usize])),
+	});
+	hash
+}
+
+// Benchmarks the runtime can't set up are weighed at the maximum instead.
+fn unsupported() -> BenchmarkError {
+	BenchmarkError::Override(BenchmarkResult::from_weight(Weight::MAX))
+}
 
 benchmarks! {
-	do_something {
-		let s in 0 .. 100;
+	add_manufacturer {
+		let m in 0 .. T::MaxManufacturers::get() - 1;
+		let mut all: Vec<T::AccountId> = (0..m).map(|i| account("manufacturer", i, 0)).collect();
+		all.sort();
+		Manufacturer::<T>::put(BoundedVec::truncate_from(all));
+		let who: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Root, who.clone())
+	verify {
+		assert!(Manufacturer::<T>::get().binary_search(&who).is_ok());
+	}
+
+	add_product {
+		let caller = manufacturer::<T>();
+		let hash = product::<T>(0);
+	}: _(RawOrigin::Signed(caller.clone()), hash)
+	verify {
+		assert_eq!(ProductManufacturer::<T>::get(&hash), Some(caller));
+	}
+
+	check_authenticity {
 		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		let hash = product::<T>(0);
+		ProductsHash::<T>::insert(&hash, BlockNumberFor::<T>::zero());
+		ProductManufacturer::<T>::insert(&hash, seller::<T>(1));
+	}: _(RawOrigin::Signed(caller.clone()), hash)
+	verify {
+		assert_eq!(ProductOwner::<T>::get(&hash), Some(caller));
+	}
+
+	refund_products {
+		let caller: T::AccountId = whitelisted_caller();
+		let maker = seller::<T>(1);
+		let hash = product::<T>(0);
+		let deadline: MomentOf<T> = 1u32.into();
+		PartialSellProduct::<T>::insert(&hash, deadline);
+		T::Currency::reserve(&maker, T::FinalizeDeposit::get())?;
+		FinalizeDeposits::<T>::insert(&hash, (&maker, T::FinalizeDeposit::get()));
+		ProductOwner::<T>::insert(&hash, &caller);
+		ProductManufacturer::<T>::insert(&hash, &maker);
+	}: _(RawOrigin::Signed(caller.clone()), hash)
+	verify {
+		assert_eq!(ReturnedBy::<T>::get(&hash), Some(caller));
+	}
+
+	set_catalogue_endpoint {
+		let caller = manufacturer::<T>();
+		let endpoint = sp_std::vec![b'a'; T::MaxEndpointLength::get() as usize];
+	}: _(RawOrigin::Signed(caller.clone()), endpoint)
+	verify {
+		assert!(CatalogueEndpoint::<T>::contains_key(&caller));
+	}
+
+	open_dispute {
+		let caller: T::AccountId = whitelisted_caller();
+		let deposit = T::DisputeDeposit::get();
+		T::Currency::make_free_balance_be(&caller, T::Currency::minimum_balance().saturating_add(deposit));
+		let hash = product::<T>(0);
+		ReturnedProducts::<T>::insert(&hash, BlockNumberFor::<T>::zero());
+		ReturnedBy::<T>::insert(&hash, &caller);
+		ProductManufacturer::<T>::insert(&hash, account::<T::AccountId>("manufacturer", 0, 0));
+
+		// The disputes lapsing in the same block fill all but one slot.
+		let deadline = frame_system::Pallet::<T>::block_number() + T::ArbitrationPeriod::get();
+		let lapsing: Vec<T::Hash> = (1..T::MaxDisputesPerBlock::get()).map(product::<T>).collect();
+		DisputeDeadlines::<T>::insert(deadline, BoundedVec::truncate_from(lapsing));
+		let evidence = sp_std::vec![0; T::MaxCidLength::get() as usize];
+	}: _(RawOrigin::Signed(caller), hash, evidence)
+	verify {
+		assert!(Disputes::<T>::contains_key(&hash));
+	}
+
+	set_return_period {
+		let period: MomentOf<T> = 1_000u32.into();
+	}: _(RawOrigin::Root, Some(period))
+	verify {
+		assert_eq!(ReturnPolicy::<T>::get(), Some(period));
+	}
+
+	resolve_dispute {
+		let origin = T::ArbiterOrigin::try_successful_origin().map_err(|_| unsupported())?;
+
+		// The manufacturer contests a refund the arbiter upholds, so the deposit changes hands
+		// and the product goes back on sale.
+		let customer: T::AccountId = account("customer", 0, 0);
+		let maker: T::AccountId = account("manufacturer", 0, 0);
+		let deposit = T::DisputeDeposit::get();
+		T::Currency::make_free_balance_be(&maker, T::Currency::minimum_balance().saturating_add(deposit));
+		T::Currency::reserve(&maker, deposit)?;
+		let hash = product::<T>(0);
+		ReturnedProducts::<T>::insert(&hash, BlockNumberFor::<T>::zero());
+		ReturnedBy::<T>::insert(&hash, &customer);
+		ProductManufacturer::<T>::insert(&hash, &maker);
+		ProductOwner::<T>::insert(&hash, &customer);
+		RegisteredAt::<T>::insert(BlockNumberFor::<T>::zero(), &hash, ());
+		RegistrationBlock::<T>::insert(&hash, BlockNumberFor::<T>::zero());
+
+		// Its deadline is the last of a full block.
+		let deadline = frame_system::Pallet::<T>::block_number() + T::ArbitrationPeriod::get();
+		let mut lapsing: Vec<T::Hash> = (1..T::MaxDisputesPerBlock::get()).map(product::<T>).collect();
+		lapsing.push(hash);
+		DisputeDeadlines::<T>::insert(deadline, BoundedVec::truncate_from(lapsing));
+		Disputes::<T>::insert(&hash, Dispute {
+			opener: maker.clone(),
+			customer,
+			manufacturer: maker,
+			evidence: BoundedVec::truncate_from(sp_std::vec![0; T::MaxCidLength::get() as usize]),
+			deposit,
+			deadline,
+		});
+	}: _<T::RuntimeOrigin>(origin, hash, Ruling::Customer)
+	verify {
+		let now = frame_system::Pallet::<T>::block_number();
+		assert!(ProductsHash::<T>::contains_key(&hash));
+		assert!(RegisteredAt::<T>::contains_key(now, &hash));
+	}
+
+	record_verification {
+		// A sold product is looked up in every other lifecycle set first.
+		let hash = product::<T>(0);
+		SellProducts::<T>::insert(&hash, BlockNumberFor::<T>::zero());
+		LastVerified::<T>::insert(&hash, BlockNumberFor::<T>::zero());
+		// Other products filled the block up to the last verification it takes.
+		let verified = T::MaxVerificationsPerBlock::get().saturating_sub(1);
+		VerificationsInBlock::<T>::put((frame_system::Pallet::<T>::block_number(), verified));
+	}: _(RawOrigin::None, hash)
+	verify {
+		assert_eq!(VerificationCount::<T>::get(&hash), 1);
+		assert_eq!(VerificationsInBlock::<T>::get().1, verified + 1);
+	}
+
+	fund_sponsorship {
+		let caller = manufacturer::<T>();
+		let amount = T::Currency::minimum_balance().saturating_mul(10u32.into());
+		T::Currency::make_free_balance_be(&caller, amount.saturating_mul(2u32.into()));
+	}: _(RawOrigin::Signed(caller.clone()), amount)
+	verify {
+		assert_eq!(Template::<T>::sponsorship(&caller), amount);
+	}
+
+	withdraw_sponsorship {
+		let caller: T::AccountId = whitelisted_caller();
+		let amount = T::Currency::minimum_balance().saturating_mul(10u32.into());
+		T::Currency::make_free_balance_be(&Template::<T>::sponsor_account(&caller), amount);
+	}: _(RawOrigin::Signed(caller.clone()), amount)
+	verify {
+		assert!(Template::<T>::sponsorship(&caller).is_zero());
+	}
+
+	set_warranty_period {
+		let period: MomentOf<T> = 1_000u32.into();
+	}: _(RawOrigin::Root, 7, period)
+	verify {
+		assert_eq!(WarrantyPeriod::<T>::get(7), Some(period));
+	}
+
+	set_product_category {
+		let caller: T::AccountId = whitelisted_caller();
+		let hash = product::<T>(0);
+		ProductManufacturer::<T>::insert(&hash, &caller);
+	}: _(RawOrigin::Signed(caller), hash, 7)
+	verify {
+		assert_eq!(ProductCategory::<T>::get(&hash), 7);
+	}
+
+	register_warranty {
+		// The return window is over but nobody finalized the sale, so registering does.
+		let caller: T::AccountId = whitelisted_caller();
+		let maker = seller::<T>(1);
+		let hash = product::<T>(0);
+		let deadline: MomentOf<T> = 1u32.into();
+		PartialSellProduct::<T>::insert(&hash, deadline);
+		T::Currency::reserve(&maker, T::FinalizeDeposit::get())?;
+		FinalizeDeposits::<T>::insert(&hash, (&maker, T::FinalizeDeposit::get()));
+		ProductManufacturer::<T>::insert(&hash, &maker);
+		ProductOwner::<T>::insert(&hash, &caller);
+		WarrantyPeriod::<T>::insert(0, MomentOf::<T>::from(1_000u32));
+		pallet_timestamp::Pallet::<T>::set_timestamp(deadline);
+	}: _(RawOrigin::Signed(caller), hash)
+	verify {
+		assert!(Warranties::<T>::contains_key(&hash));
+	}
+
+	file_warranty_claim {
+		let caller: T::AccountId = whitelisted_caller();
+		let hash = product::<T>(0);
+		Warranties::<T>::insert(&hash, Warranty {
+			owner: caller.clone(),
+			expires: 1_000u32.into(),
+			status: WarrantyStatus::Active,
+			claim_reason: None,
+		});
+		let reason = sp_std::vec![0; T::MaxCidLength::get() as usize];
+	}: _(RawOrigin::Signed(caller), hash, reason)
+	verify {
+		assert_eq!(Warranties::<T>::get(&hash).map(|warranty| warranty.status), Some(WarrantyStatus::Claimed));
+	}
+
+	accept_warranty_claim {
+		let caller: T::AccountId = whitelisted_caller();
+		let hash = claimed_warranty::<T>(&caller);
+	}: _(RawOrigin::Signed(caller), hash)
+	verify {
+		assert_eq!(Warranties::<T>::get(&hash).map(|warranty| warranty.status), Some(WarrantyStatus::Accepted));
+	}
+
+	reject_warranty_claim {
+		let caller: T::AccountId = whitelisted_caller();
+		let hash = claimed_warranty::<T>(&caller);
+	}: _(RawOrigin::Signed(caller), hash)
+	verify {
+		assert_eq!(Warranties::<T>::get(&hash).map(|warranty| warranty.status), Some(WarrantyStatus::Rejected));
+	}
+
+	finalize_expired {
+		let n in 1 .. MAX_FINALIZE_BATCH;
+		let caller: T::AccountId = whitelisted_caller();
+		let maker = seller::<T>(n);
+
+		// `n` sales whose return window is over, each with a deadline of its own.
+		let hashes: Vec<T::Hash> = (0..n).map(product::<T>).collect();
+		let mut deadline: MomentOf<T> = Zero::zero();
+		for (i, hash) in hashes.iter().enumerate() {
+			deadline = ((i as u32 + 1) * 1_000_000).into();
+			PartialSellProduct::<T>::insert(hash, deadline);
+			T::Currency::reserve(&maker, T::FinalizeDeposit::get())?;
+			FinalizeDeposits::<T>::insert(hash, (&maker, T::FinalizeDeposit::get()));
+			ProductManufacturer::<T>::insert(hash, &maker);
+		}
+		pallet_timestamp::Pallet::<T>::set_timestamp(deadline);
+	}: _(RawOrigin::Signed(caller), hashes)
+	verify {
+		assert_eq!(SellProducts::<T>::iter_keys().count() as u32, n);
+	}
+
+	on_initialize {
+		let d in 0 .. T::MaxDisputesPerBlock::get();
+		let n: BlockNumberFor<T> = 10u32.into();
+		let maker: T::AccountId = account("manufacturer", 0, 0);
+
+		// `d` disputes the arbiter didn't rule on in time.
+		let customer: T::AccountId = account("customer", 0, 0);
+		let disputed: Vec<T::Hash> = (0..d).map(product::<T>).collect();
+		for hash in disputed.iter() {
+			ReturnedProducts::<T>::insert(hash, BlockNumberFor::<T>::zero());
+			ReturnedBy::<T>::insert(hash, &customer);
+			ProductManufacturer::<T>::insert(hash, &maker);
+			Disputes::<T>::insert(hash, Dispute {
+				opener: customer.clone(),
+				customer: customer.clone(),
+				manufacturer: maker.clone(),
+				evidence: BoundedVec::truncate_from(sp_std::vec![0; T::MaxCidLength::get() as usize]),
+				deposit: Zero::zero(),
+				deadline: n,
+			});
+		}
+		DisputeDeadlines::<T>::insert(n, BoundedVec::truncate_from(disputed));
+	}: {
+		Template::<T>::on_initialize(n);
+	}
+	verify {
+		assert_eq!(ReturnedProducts::<T>::iter_keys().count(), 0);
+	}
+
+	query_product_status {
+		let querier = T::ReachableQuerier::get().ok_or_else(unsupported)?;
+		// An unknown product is looked up in every status map.
+		let hash = product::<T>(0);
+		let response = ResponseCall {
+			pallet_index: u8::MAX,
+			call_index: u8::MAX,
+			max_weight: T::MaxResponseWeight::get(),
+		};
+	}: {
+		Template::<T>::answer_status_query(querier, 0, hash, response)?;
+	}
+	verify {
+		let event: <T as Config>::RuntimeEvent =
+			Event::<T>::ProductStatusQueried { querier, query_id: 0, hash, status: None }.into();
+		frame_system::Pallet::<T>::assert_last_event(event.into());
+	}
+
+	product_status_response {
+		let origin = T::XcmOrigin::try_successful_origin().map_err(|_| unsupported())?;
+		let responder = T::XcmOrigin::try_origin(origin.clone()).map_err(|_| unsupported())?;
+		let hash = product::<T>(0);
+		StatusQueries::<T>::insert(0, (responder, hash));
+	}: _<T::RuntimeOrigin>(origin, 0, hash, Some(ProductStatus::Sold))
+	verify {
+		assert!(!StatusQueries::<T>::contains_key(0));
+		let event: <T as Config>::RuntimeEvent = Event::<T>::ProductStatusReceived {
+			responder,
+			query_id: 0,
+			hash,
+			status: Some(ProductStatus::Sold),
+		}
+		.into();
+		frame_system::Pallet::<T>::assert_last_event(event.into());
+	}
+
+	request_product_status {
+		let dest = T::ReachableQuerier::get().ok_or_else(unsupported)?;
+		let hash = product::<T>(0);
+		let query = ResponseCall {
+			pallet_index: u8::MAX,
+			call_index: u8::MAX,
+			max_weight: T::MaxResponseWeight::get(),
+		};
+	}: _(RawOrigin::Root, Box::new(dest), hash, query)
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert_eq!(StatusQueries::<T>::get(0), Some((dest, hash)));
 	}
 
 	impl_benchmark_test_suite!(Template, crate::mock::new_test_ext(), crate::mock::Test);
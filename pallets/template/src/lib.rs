@@ -7,58 +7,585 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod xcm_mock;
+
+#[cfg(test)]
+mod xcm_tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+pub mod migrations;
+
+pub mod sponsorship;
+pub use sponsorship::ChargeSponsoredTransactionPayment;
+
+use sp_core::crypto::KeyTypeId;
+
+/// Key type of the accounts the offchain worker signs catalogue submissions with.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"auth");
+
+/// Crypto used by the offchain worker to sign `add_products` transactions on behalf of a
+/// manufacturer.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct CatalogueAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for CatalogueAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for CatalogueAuthId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::inherent::Vec;
+	use codec::{DecodeAll, FullCodec};
+	use frame_support::log;
 	use frame_support::pallet;
 	use frame_support::pallet_prelude::*;
-	use frame_system::pallet_prelude::*;
+	use frame_support::traits::{
+		BalanceStatus, Currency, ExistenceRequirement, PalletInfoAccess, ReservableCurrency,
+	};
+	use frame_support::PalletId;
+	use crate::WeightInfo;
+	use frame_system::{
+		offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+		pallet_prelude::*,
+	};
+	use sp_runtime::{
+		offchain::{
+			http,
+			storage::{MutateStorageError, StorageRetrievalError, StorageValueRef},
+			Duration,
+		},
+		traits::{AccountIdConversion, Hash as HashT, IdentifyAccount, One, Saturating, Zero},
+		RuntimeAppPublic,
+	};
+	use sp_std::{boxed::Box, vec::Vec};
+	use xcm::latest::{
+		send_xcm,
+		Instruction::{Transact, UnpaidExecution},
+		MultiLocation, OriginKind, QueryId, SendXcm, WeightLimit, Xcm,
+	};
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Wall-clock time in milliseconds, as kept by `pallet_timestamp`.
+	pub type MomentOf<T> = <T as pallet_timestamp::Config>::Moment;
+
+	/// CID of an off-chain document, such as dispute evidence or a manufacturer profile.
+	pub type CidOf<T> = BoundedVec<u8, <T as Config>::MaxCidLength>;
+
+	/// A dispute as kept in storage.
+	pub type DisputeOf<T> =
+		Dispute<<T as frame_system::Config>::AccountId, BalanceOf<T>, BlockNumberFor<T>, CidOf<T>>;
+
+	/// A warranty as kept in storage.
+	pub type WarrantyOf<T> = Warranty<<T as frame_system::Config>::AccountId, MomentOf<T>, CidOf<T>>;
+
+	/// Which side of a contested refund the arbiter ruled for.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum Ruling {
+		/// The refund stands and the product goes back to the manufacturer's stock.
+		Customer,
+		/// The refund is rejected and the sale is final.
+		Manufacturer,
+	}
+
+	/// A dispute opened on a returned product.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Dispute<AccountId, Balance, BlockNumber, Evidence = Vec<u8>> {
+		/// Who opened the dispute and put down the deposit.
+		pub opener: AccountId,
+		/// The customer who returned the product.
+		pub customer: AccountId,
+		/// The manufacturer who registered the product.
+		pub manufacturer: AccountId,
+		/// CID of the evidence supporting the opener's claim.
+		pub evidence: Evidence,
+		/// Deposit reserved from the opener.
+		pub deposit: Balance,
+		/// Block after which the dispute is resolved in the customer's favour.
+		pub deadline: BlockNumber,
+	}
+
+	/// Where a product currently is in its lifecycle.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum ProductStatus {
+		/// Registered by the manufacturer and not sold yet.
+		Registered,
+		/// Sold, but still inside the return window.
+		PendingReturn,
+		/// Sold and the return window is over.
+		Sold,
+		/// Returned and waiting for the manufacturer's inspection.
+		Returned,
+	}
+
+	/// The call a cross-chain status query is answered with on the querying chain, by pallet and
+	/// call index. It gets `(query_id, hash, status)` as arguments, like `product_status_response`.
+	/// `request_product_status` names the `query_product_status` of the chain asked with it too.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct ResponseCall {
+		/// Index of the pallet in the querying chain's runtime.
+		pub pallet_index: u8,
+		/// Index of the call in the pallet.
+		pub call_index: u8,
+		/// Weight the querying chain allows the call to take.
+		pub max_weight: Weight,
+	}
+
+	/// Category a product belongs to, used to look up its warranty length.
+	pub type CategoryId = u32;
+
+	/// A page of products, with the cursor to pass back for the next page.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct ProductPage<Hash, Cursor> {
+		/// Products of the page, in storage order.
+		pub products: Vec<Hash>,
+		/// Where the next page starts, `None` on the last page.
+		pub next: Option<Cursor>,
+	}
+
+	/// Number of a manufacturer's products in each lifecycle status.
+	#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct ProductCounts {
+		/// Registered and not sold.
+		pub registered: u32,
+		/// Sold and inside the return window.
+		pub pending: u32,
+		/// Sold and past the return window.
+		pub sold: u32,
+		/// Returned and waiting for inspection.
+		pub returned: u32,
+		/// Recalled by the manufacturer. The pallet has no recalls yet, so this stays zero.
+		pub recalled: u32,
+	}
+
+	impl ProductCounts {
+		fn count_mut(&mut self, status: ProductStatus) -> &mut u32 {
+			match status {
+				ProductStatus::Registered => &mut self.registered,
+				ProductStatus::PendingReturn => &mut self.pending,
+				ProductStatus::Sold => &mut self.sold,
+				ProductStatus::Returned => &mut self.returned,
+			}
+		}
+
+		/// Count a product leaving status `from`, if any, and entering status `to`.
+		pub fn transition(&mut self, from: Option<ProductStatus>, to: ProductStatus) {
+			if let Some(from) = from {
+				let count = self.count_mut(from);
+				*count = count.saturating_sub(1);
+			}
+			let count = self.count_mut(to);
+			*count = count.saturating_add(1);
+		}
+	}
+
+	/// Position in a registration block range: the last product read in a block, or `None` once
+	/// the whole block was read.
+	pub type RangeCursor<BlockNumber, Hash> = (BlockNumber, Option<Hash>);
+
+	/// State of a product's warranty.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum WarrantyStatus {
+		/// Registered and no claim is pending.
+		Active,
+		/// A claim was filed and waits for the manufacturer.
+		Claimed,
+		/// The manufacturer accepted the claim.
+		Accepted,
+		/// The manufacturer rejected the claim. A new claim can be filed.
+		Rejected,
+	}
+
+	/// A warranty registered by the owner of a sold product.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct Warranty<AccountId, Moment, Reason = Vec<u8>> {
+		/// Owner who registered the warranty.
+		pub owner: AccountId,
+		/// Last moment (ms since the Unix epoch) at which a claim can be filed.
+		pub expires: Moment,
+		/// Current state of the warranty.
+		pub status: WarrantyStatus,
+		/// CID of the reason given with the last claim.
+		pub claim_reason: Option<Reason>,
+	}
+
+	impl<AccountId, Moment, Reason: Into<Vec<u8>>> Warranty<AccountId, Moment, Reason> {
+		/// The same warranty with its claim reason as a plain `Vec`, as the runtime API returns it.
+		pub fn into_unbounded(self) -> Warranty<AccountId, Moment> {
+			Warranty {
+				owner: self.owner,
+				expires: self.expires,
+				status: self.status,
+				claim_reason: self.claim_reason.map(Into::into),
+			}
+		}
+	}
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config{
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config + pallet_timestamp::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-		/// TimeDuration to return products
-		type expire_time : Get<u32>;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+
+		/// Milliseconds a customer has to return a product after buying it, unless
+		/// `ReturnPolicy` overrides it.
+		#[pallet::constant]
+		type ReturnPeriod: Get<MomentOf<Self>>;
+
+		/// Currency used to hold dispute and finalization deposits.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Origin allowed to rule on disputes.
+		type ArbiterOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Deposit reserved from whoever opens a dispute.
+		#[pallet::constant]
+		type DisputeDeposit: Get<BalanceOf<Self>>;
+
+		/// Deposit reserved from the manufacturer when one of its products is sold, paid to
+		/// whoever finalizes the sale with `finalize_expired` once its return window is over.
+		#[pallet::constant]
+		type FinalizeDeposit: Get<BalanceOf<Self>>;
+
+		/// Number of blocks the arbiter has to rule before the dispute is resolved in the
+		/// customer's favour.
+		#[pallet::constant]
+		type ArbitrationPeriod: Get<BlockNumberFor<Self>>;
+
+		/// Keys the offchain worker signs catalogue submissions with.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		/// Maximum number of products registered by a single `add_products` call.
+		#[pallet::constant]
+		type MaxCatalogueBatch: Get<u32>;
+
+		/// Number of blocks between two catalogue syncs of the offchain worker.
+		#[pallet::constant]
+		type CatalogueSyncInterval: Get<BlockNumberFor<Self>>;
+
+		/// Priority of unsigned verification transactions in the pool. Keep it below the priority
+		/// of signed transactions, which pay for their place in the block.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// Maximum number of unsigned verifications recorded in a single block.
+		#[pallet::constant]
+		type MaxVerificationsPerBlock: Get<u32>;
+
+		/// Identifier the manufacturers' sponsorship accounts are derived from.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Maximum number of transactions a manufacturer pays for on a single product.
+		#[pallet::constant]
+		type MaxSponsoredPerProduct: Get<u32>;
+
+		/// Maximum number of transactions a manufacturer pays for on behalf of a single customer.
+		#[pallet::constant]
+		type MaxSponsoredPerCustomer: Get<u32>;
+
+		/// Maximum number of products, and of storage entries read, for one page of a product
+		/// listing.
+		#[pallet::constant]
+		type MaxPageSize: Get<u32>;
+
+		/// Maximum number of registered manufacturers.
+		#[pallet::constant]
+		type MaxManufacturers: Get<u32>;
+
+		/// Maximum number of disputes whose arbitration period ends in the same block.
+		#[pallet::constant]
+		type MaxDisputesPerBlock: Get<u32>;
+
+		/// Longest CID kept in storage: dispute evidence, warranty claim reasons and manufacturer
+		/// metadata.
+		#[pallet::constant]
+		type MaxCidLength: Get<u32>;
+
+		/// Longest catalogue endpoint URL a manufacturer can set.
+		#[pallet::constant]
+		type MaxEndpointLength: Get<u32>;
+
+		/// Origin of cross-chain status queries and of their answers, resolving to the location
+		/// of the chain that sent them.
+		type XcmOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = MultiLocation>;
+
+		/// Sends cross-chain status queries and their answers.
+		type XcmSender: SendXcm;
+
+		/// Most weight the `response` call of a status query may ask the querying chain to
+		/// allow. Answers go out unpaid, so queriers can't make them arbitrarily heavy.
+		#[pallet::constant]
+		type MaxResponseWeight: Get<Weight>;
+
+		/// A location `XcmSender` can deliver messages to, to benchmark `query_product_status` and
+		/// `request_product_status` with. `None` skips those benchmarks.
+		#[cfg(feature = "runtime-benchmarks")]
+		type ReachableQuerier: Get<Option<MultiLocation>>;
 	}
 
+	/// Longest preimage `check_authenticity_with_preimage` hashes.
+	pub const MAX_PREIMAGE_LENGTH: u32 = 256;
+
+	/// Most sales `finalize_expired` takes at once.
+	pub const MAX_FINALIZE_BATCH: u32 = 100;
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(7);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
+	/// Products registered and not sold yet, with the block they were registered at. A product
+	/// put back on sale by a dispute counts as registered again at that block.
 	#[pallet::storage]
 	#[pallet::getter(fn productshash)]
-	pub type ProductsHash<T: Config> = StorageValue<_, Vec<T::Hash>, ValueQuery>;
+	pub type ProductsHash<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, BlockNumberFor<T>, OptionQuery>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn manufacturers)]
-	pub type Manufacturer<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+	pub type Manufacturer<T: Config> = StorageValue<_, BoundedVec<T::AccountId, T::MaxManufacturers>, ValueQuery>;
 
+	/// Sold products still inside the return window, with the moment (ms) the window closes at.
 	#[pallet::storage]
 	#[pallet::getter(fn partialsellproduct)]
-	pub type PartialSellProduct<T: Config> = StorageValue<_, Vec<T::Hash>, ValueQuery>;
+	pub type PartialSellProduct<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, MomentOf<T>, OptionQuery>;
+
+	/// Who reserved the finalization deposit of a pending sale, and how much.
+	#[pallet::storage]
+	#[pallet::getter(fn finalizedeposit)]
+	pub type FinalizeDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::Hash, (T::AccountId, BalanceOf<T>), OptionQuery>;
 
+	/// Products whose sale is final, with the block it became final at.
 	#[pallet::storage]
 	#[pallet::getter(fn selledproducts)]
-	pub type SellProducts<T: Config> = StorageValue<_, Vec<T::Hash>, ValueQuery>;
+	pub type SellProducts<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, BlockNumberFor<T>, OptionQuery>;
 
+	/// Returned products waiting for inspection, with the block they were returned at.
 	#[pallet::storage]
 	#[pallet::getter(fn retunedproducts)]
-	pub type ReturnedProducts<T: Config> = StorageValue<_,Vec<T::Hash>, ValueQuery>;
+	pub type ReturnedProducts<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, BlockNumberFor<T>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn productmanufacturer)]
+	pub type ProductManufacturer<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn returnedby)]
+	pub type ReturnedBy<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn disputes)]
+	pub type Disputes<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, DisputeOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn disputedeadlines)]
+	pub type DisputeDeadlines<T: Config> =
+		StorageMap<_, Blake2_128Concat, BlockNumberFor<T>, BoundedVec<T::Hash, T::MaxDisputesPerBlock>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn productowner)]
+	pub type ProductOwner<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn productcategory)]
+	pub type ProductCategory<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, CategoryId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn warrantyperiod)]
+	pub type WarrantyPeriod<T: Config> = StorageMap<_, Blake2_128Concat, CategoryId, MomentOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn warranties)]
+	pub type Warranties<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, WarrantyOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn catalogueendpoint)]
+	pub type CatalogueEndpoint<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<u8, T::MaxEndpointLength>, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn lastverified)]
+	pub type LastVerified<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, BlockNumberFor<T>, OptionQuery>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn partialproducttime)]
-	pub type PartialProductTime<T: Config> = StorageMap<_, Blake2_128Concat, BlockNumberFor<T>, T::Hash, ValueQuery>;
+	#[pallet::getter(fn verificationcount)]
+	pub type VerificationCount<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, u32, ValueQuery>;
+
+	/// Block of the last unsigned verification and the number recorded in it.
+	#[pallet::storage]
+	#[pallet::getter(fn verificationsinblock)]
+	pub type VerificationsInBlock<T: Config> = StorageValue<_, (BlockNumberFor<T>, u32), ValueQuery>;
+
+	/// Status queries sent with `request_product_status` and not answered yet: the location
+	/// expected to answer and the product asked about.
+	#[pallet::storage]
+	#[pallet::getter(fn statusqueries)]
+	pub type StatusQueries<T: Config> =
+		StorageMap<_, Blake2_128Concat, QueryId, (MultiLocation, T::Hash), OptionQuery>;
+
+	/// Id of the next status query sent with `request_product_status`.
+	#[pallet::storage]
+	#[pallet::getter(fn nextqueryid)]
+	pub type NextQueryId<T: Config> = StorageValue<_, QueryId, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn sponsoredperproduct)]
+	pub type SponsoredPerProduct<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, u32, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn sponsoredpercustomer)]
+	pub type SponsoredPerCustomer<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AccountId,
+		u32,
+		ValueQuery,
+	>;
+
+	/// Metadata describing a manufacturer, e.g. the CID of its profile.
+	#[pallet::storage]
+	#[pallet::getter(fn manufacturermetadata)]
+	pub type ManufacturerMetadata<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, CidOf<T>, OptionQuery>;
+
+	/// Products registered by a manufacturer.
+	#[pallet::storage]
+	#[pallet::getter(fn manufacturerproducts)]
+	pub type ManufacturerProducts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::Hash,
+		(),
+		OptionQuery,
+	>;
+
+	/// Number of a manufacturer's products in each lifecycle status.
+	#[pallet::storage]
+	#[pallet::getter(fn manufacturercounts)]
+	pub type ManufacturerCounts<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ProductCounts, ValueQuery>;
+
+	/// Return period set by root, overriding `Config::ReturnPeriod` for future sales.
+	#[pallet::storage]
+	#[pallet::getter(fn returnpolicy)]
+	pub type ReturnPolicy<T: Config> = StorageValue<_, MomentOf<T>, OptionQuery>;
+
+	/// Products registered as a commitment to a preimage the customer reveals when buying them.
+	#[pallet::storage]
+	#[pallet::getter(fn committedproducts)]
+	pub type CommittedProducts<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, (), OptionQuery>;
+
+	/// Products registered at a given block.
+	#[pallet::storage]
+	#[pallet::getter(fn registeredat)]
+	pub type RegisteredAt<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		Blake2_128Concat,
+		T::Hash,
+		(),
+		OptionQuery,
+	>;
+
+	/// The block each product is indexed under in `RegisteredAt`, kept after it is sold.
+	#[pallet::storage]
+	#[pallet::getter(fn registrationblock)]
+	pub type RegistrationBlock<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, BlockNumberFor<T>, OptionQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Manufacturers registered at genesis, with their metadata.
+		pub manufacturers: Vec<(T::AccountId, Vec<u8>)>,
+		/// Products registered at genesis, with the manufacturer that produced them.
+		pub products: Vec<(T::AccountId, T::Hash)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { manufacturers: Default::default(), products: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let mut all_manufacturer = BoundedVec::<T::AccountId, T::MaxManufacturers>::default();
+			for (who, metadata) in self.manufacturers.iter() {
+				let location = all_manufacturer.binary_search(who).expect_err("duplicate manufacturer in genesis");
+				all_manufacturer.try_insert(location, who.clone()).expect("too many manufacturers in genesis");
+				let metadata: CidOf<T> = metadata.clone().try_into().expect("genesis manufacturer metadata too long");
+				ManufacturerMetadata::<T>::insert(who, metadata);
+			}
+			Manufacturer::<T>::put(&all_manufacturer);
+
+			for (who, hash) in self.products.iter() {
+				assert!(all_manufacturer.binary_search(who).is_ok(), "genesis product of an unknown manufacturer");
+				Pallet::<T>::do_add_product(who, *hash).expect("duplicate product in genesis");
+			}
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		ProductAdded,
-		ManufacturerAdded,
-		AuthenticProduct,
+		ProductAdded { hash: T::Hash, manufacturer: T::AccountId },
+		ManufacturerAdded { who: T::AccountId },
+		AuthenticProduct { hash: T::Hash, who: T::AccountId },
+		SaleFinalized { hash: T::Hash },
+		ProductReturned { hash: T::Hash, who: T::AccountId },
+		DisputeOpened { hash: T::Hash, who: T::AccountId, deadline: BlockNumberFor<T> },
+		DisputeResolved { hash: T::Hash, ruling: Ruling },
+		WarrantyPeriodSet { category: CategoryId, period: MomentOf<T> },
+		ProductCategorySet { hash: T::Hash, category: CategoryId },
+		WarrantyRegistered { hash: T::Hash, owner: T::AccountId, expires: MomentOf<T> },
+		WarrantyClaimFiled { hash: T::Hash, owner: T::AccountId },
+		WarrantyClaimAccepted { hash: T::Hash },
+		WarrantyClaimRejected { hash: T::Hash },
+		CatalogueEndpointSet { who: T::AccountId },
+		ProductVerified { hash: T::Hash, status: ProductStatus },
+		SponsorshipFunded { who: T::AccountId, amount: BalanceOf<T> },
+		SponsorshipWithdrawn { who: T::AccountId, amount: BalanceOf<T> },
+		FeeSponsored { hash: T::Hash, manufacturer: T::AccountId, who: T::AccountId, fee: BalanceOf<T> },
+		ReturnPeriodSet { period: Option<MomentOf<T>> },
+		ProductStatusQueried { querier: MultiLocation, query_id: QueryId, hash: T::Hash, status: Option<ProductStatus> },
+		ProductStatusReceived { responder: MultiLocation, query_id: QueryId, hash: T::Hash, status: Option<ProductStatus> },
+		StatusQuerySent { responder: MultiLocation, query_id: QueryId, hash: T::Hash },
 	}
 
 	// Error inform users that something went wrong.
@@ -74,6 +601,62 @@ pub mod pallet {
 		UnAuthenticProduct,
 		// If product was not sold.
 		UnsoldProduct,
+		// If product is not waiting for inspection after a return.
+		NotReturnedProduct,
+		// If caller is neither the customer nor the manufacturer of the returned product.
+		NotDisputeParty,
+		// If a dispute is already open for this product.
+		DisputeAlreadyOpen,
+		// If there is no open dispute for this product.
+		NoDispute,
+		// If caller is not the owner of the product.
+		NotProductOwner,
+		// If the product's category has no warranty.
+		NoWarrantyForCategory,
+		// If a warranty is already registered for this product.
+		WarrantyAlreadyRegistered,
+		// If no warranty is registered for this product.
+		NoWarranty,
+		// If the warranty is over.
+		WarrantyExpired,
+		// If the warranty is not in a state that allows this action.
+		InvalidWarrantyStatus,
+		// If more products are submitted at once than the call takes.
+		TooManyProducts,
+		// If the product was already verified in this block.
+		AlreadyVerified,
+		// If the manufacturer withdraws more than its sponsorship balance.
+		InsufficientSponsorship,
+		// If a committed product is bought without revealing its preimage.
+		PreimageRequired,
+		// If the preimage is longer than `MAX_PREIMAGE_LENGTH`.
+		PreimageTooLong,
+		// If the return period is zero.
+		InvalidReturnPeriod,
+		// If the return window of the product is over.
+		ReturnWindowClosed,
+		// If none of the products given has a sale whose return window is over.
+		NothingToFinalize,
+		// If `MaxManufacturers` manufacturers are already registered.
+		TooManyManufacturers,
+		// If `MaxDisputesPerBlock` disputes already end their arbitration period in the same block.
+		TooManyDisputes,
+		// If a CID is longer than `MaxCidLength`.
+		CidTooLong,
+		// If a catalogue endpoint is longer than `MaxEndpointLength`.
+		EndpointTooLong,
+		// If the answer to a cross-chain status query couldn't be sent back.
+		ResponseNotSent,
+		// If the answer to a status query is allowed more than `MaxResponseWeight`.
+		ResponseTooHeavy,
+		// If `MaxVerificationsPerBlock` verifications were already recorded in this block.
+		TooManyVerifications,
+		// If a status query couldn't be sent to the chain asked.
+		QueryNotSent,
+		// If an answer comes for a status query this chain didn't send or already got an answer to.
+		UnknownQuery,
+		// If an answer comes from another chain than the one asked, or about another product.
+		UnexpectedResponse,
 	}
 
 	// Hooks
@@ -82,17 +665,29 @@ pub mod pallet {
 	#[pallet::hooks]
 	impl<T:Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
 		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
-			let is_expire = PartialProductTime::<T>::contains_key(n);
+			// Arbiter missed the deadline, so the refund stands.
+			let lapsed = DisputeDeadlines::<T>::take(n);
+			for hash in lapsed.iter() {
+				// A dispute opened again after a ruling has a deadline of its own.
+				if let Some(dispute) = Disputes::<T>::get(hash).filter(|dispute| dispute.deadline == n) {
+					Disputes::<T>::remove(hash);
+					Self::settle_dispute(*hash, dispute, Ruling::Customer);
+				}
+			}
 
-			if is_expire {
-				let expire_product = PartialProductTime::<T>::get(n);
-				// Remove the product from the partial product
-				let mut all_product = PartialSellProduct::<T>::get();
-				let location: usize = all_product.binary_search(&expire_product).unwrap();
+			<T as Config>::WeightInfo::on_initialize(lapsed.len() as u32)
+		}
 
-				all_product.remove(location);
+		// Pull newly produced products from the manufacturers' catalogues and register them.
+		fn offchain_worker(n: BlockNumberFor<T>) {
+			if let Err(e) = Self::sync_catalogues(n) {
+				log::warn!(target: "runtime::template", "catalogue sync failed: {}", e);
 			}
-			Weight::zero()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state(n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			Self::do_try_state(n)
 		}
 	}
 
@@ -101,89 +696,1068 @@ pub mod pallet {
 
 		// Add hash of the product..
 		// Only authorised person is allowed to perform this task.
-		#[pallet::weight(10_000)]
+		#[pallet::weight(<T as Config>::WeightInfo::add_product())]
 		pub fn add_product(origin: OriginFor<T>, hash: T::Hash) -> DispatchResult {
 			let who = ensure_signed(origin.clone())?;
 
 			let all_manufacturer = Manufacturer::<T>::get();
 			let _location = all_manufacturer.binary_search(&who).ok().ok_or(Error::<T>::UnAuthorisedPerson)?;
 
-			let mut all_products = ProductsHash::<T>::get();
-			let location = all_products.binary_search(&hash).err().ok_or(Error::<T>::ProductAlreadyPresent)?;
+			Self::do_add_product(&who, hash)
+		}
+
+		// Add a batch of product hashes at once.
+		// Used by the offchain worker to submit a manufacturer's catalogue.
+		#[pallet::weight(<T as Config>::WeightInfo::add_product().saturating_mul(
+			(hashes.len() as u64).min(T::MaxCatalogueBatch::get() as u64),
+		))]
+		pub fn add_products(origin: OriginFor<T>, hashes: Vec<T::Hash>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(hashes.len() <= T::MaxCatalogueBatch::get() as usize, Error::<T>::TooManyProducts);
+
+			let all_manufacturer = Manufacturer::<T>::get();
+			let _location = all_manufacturer.binary_search(&who).ok().ok_or(Error::<T>::UnAuthorisedPerson)?;
+
+			for hash in hashes {
+				Self::do_add_product(&who, hash)?;
+			}
+			Ok(())
+		}
+
+		// Set the HTTP endpoint the offchain worker polls for the caller's new products.
+		// Only authorised person is allowed to perform this task.
+		#[pallet::weight(<T as Config>::WeightInfo::set_catalogue_endpoint())]
+		pub fn set_catalogue_endpoint(origin: OriginFor<T>, endpoint: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
 
-			all_products.insert(location, hash);
+			let all_manufacturer = Manufacturer::<T>::get();
+			let _location = all_manufacturer.binary_search(&who).ok().ok_or(Error::<T>::UnAuthorisedPerson)?;
 
-			ProductsHash::<T>::put(all_products);
+			let endpoint: BoundedVec<u8, T::MaxEndpointLength> =
+				endpoint.try_into().map_err(|_| Error::<T>::EndpointTooLong)?;
+			CatalogueEndpoint::<T>::insert(&who, endpoint);
 
-			Self::deposit_event(Event::ProductAdded);
+			Self::deposit_event(Event::<T>::CatalogueEndpointSet { who });
 			Ok(())
 		}
 
-		#[pallet::weight(10_000)]
+		#[pallet::weight(<T as Config>::WeightInfo::add_manufacturer(
+			Manufacturer::<T>::decode_len().unwrap_or_default() as u32,
+		))]
 		pub fn add_manufacturer(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
 			ensure_root(origin)?;
 
 			let mut all_manufacturer = Manufacturer::<T>::get();
 			let location = all_manufacturer.binary_search(&who).err().ok_or(Error::<T>::ManufacturerAlreadyPresent)?;
 
-			all_manufacturer.insert(location, who);
+			all_manufacturer.try_insert(location, who.clone()).map_err(|_| Error::<T>::TooManyManufacturers)?;
 			Manufacturer::<T>::put(all_manufacturer);
 
-			Self::deposit_event(Event::<T>::ManufacturerAdded);
+			Self::deposit_event(Event::<T>::ManufacturerAdded { who });
 			Ok(())
 		}
 
-		#[pallet::weight(10_000)]
+		#[pallet::weight(<T as Config>::WeightInfo::check_authenticity())]
 		pub fn check_authenticity(origin: OriginFor<T>, hash: T::Hash) -> DispatchResult {
-			ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
+
+			// Committed products are only sold against their preimage.
+			ensure!(!CommittedProducts::<T>::contains_key(&hash), Error::<T>::PreimageRequired);
+
+			Self::do_sell(who, hash)
+		}
+
+		// Add a salted commitment to a product instead of its hash.
+		// The customer reveals the preimage from the product's tag with `check_authenticity_with_preimage`.
+		// Only authorised person is allowed to perform this task.
+		#[pallet::weight(<T as Config>::WeightInfo::add_product().saturating_add(T::DbWeight::get().writes(1)))]
+		pub fn add_product_commitment(origin: OriginFor<T>, commitment: T::Hash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let all_manufacturer = Manufacturer::<T>::get();
+			let _location = all_manufacturer.binary_search(&who).ok().ok_or(Error::<T>::UnAuthorisedPerson)?;
+
+			Self::do_add_product(&who, commitment)?;
+			CommittedProducts::<T>::insert(&commitment, ());
+			Ok(())
+		}
+
+		// Buy a committed product by revealing the preimage of its commitment.
+		// Once revealed the product is an ordinary one, kept under its commitment.
+		#[pallet::weight(<T as Config>::WeightInfo::check_authenticity().saturating_add(T::DbWeight::get().reads_writes(1, 1)))]
+		pub fn check_authenticity_with_preimage(origin: OriginFor<T>, preimage: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(preimage.len() <= MAX_PREIMAGE_LENGTH as usize, Error::<T>::PreimageTooLong);
+			let hash = T::Hashing::hash(&preimage);
+			CommittedProducts::<T>::take(&hash).ok_or(Error::<T>::UnAuthenticProduct)?;
+
+			Self::do_sell(who, hash)
+		}
+
+		#[pallet::weight(<T as Config>::WeightInfo::refund_products())]
+		pub fn refund_products(who: OriginFor<T>, hash: T::Hash) -> DispatchResult {
+			let customer = ensure_signed(who.clone())?;
+
+			// check this product is sold or not?
+			let refund_invalid = PartialSellProduct::<T>::get(&hash).ok_or(Error::<T>::UnsoldProduct)?;
+			// Only the customer who bought it can return it.
+			ensure!(ProductOwner::<T>::get(&hash) == Some(customer.clone()), Error::<T>::NotProductOwner);
+			// The window may be over before anyone finalized the sale.
+			ensure!(pallet_timestamp::Pallet::<T>::get() < refund_invalid, Error::<T>::ReturnWindowClosed);
+
+			// Now remove the product from partial sale, the sale won't become final.
+			PartialSellProduct::<T>::remove(&hash);
+			if let Some((depositor, deposit)) = FinalizeDeposits::<T>::take(&hash) {
+				T::Currency::unreserve(&depositor, deposit);
+			}
 
+			// Add the product into returned product storage for originality check in manufacturing unit and then again this product is available for sale.
+			ReturnedProducts::<T>::insert(&hash, frame_system::Pallet::<T>::block_number());
+			ReturnedBy::<T>::insert(&hash, &customer);
+			Self::count_transition(&hash, Some(ProductStatus::PendingReturn), ProductStatus::Returned);
+
+			Self::deposit_event(Event::<T>::ProductReturned { hash, who: customer });
+			Ok(())
+		}
+
+		// Contest the outcome of a return.
+		// Either the customer who returned the product or its manufacturer can open a dispute.
+		#[pallet::weight(<T as Config>::WeightInfo::open_dispute())]
+		pub fn open_dispute(origin: OriginFor<T>, hash: T::Hash, evidence: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(ReturnedProducts::<T>::contains_key(&hash), Error::<T>::NotReturnedProduct);
+			ensure!(!Disputes::<T>::contains_key(&hash), Error::<T>::DisputeAlreadyOpen);
+
+			let customer = ReturnedBy::<T>::get(&hash).ok_or(Error::<T>::NotReturnedProduct)?;
+			let manufacturer = ProductManufacturer::<T>::get(&hash).ok_or(Error::<T>::UnAuthenticProduct)?;
+			ensure!(who == customer || who == manufacturer, Error::<T>::NotDisputeParty);
+			let evidence: CidOf<T> = evidence.try_into().map_err(|_| Error::<T>::CidTooLong)?;
+
+			let deadline = frame_system::Pallet::<T>::block_number() + T::ArbitrationPeriod::get();
+			DisputeDeadlines::<T>::try_append(deadline, hash).map_err(|_| Error::<T>::TooManyDisputes)?;
+
+			let deposit = T::DisputeDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+
+			Disputes::<T>::insert(
+				&hash,
+				Dispute { opener: who.clone(), customer, manufacturer, evidence, deposit, deadline },
+			);
+
+			Self::deposit_event(Event::<T>::DisputeOpened { hash, who, deadline });
+			Ok(())
+		}
+
+		// Override the return period of future sales, or go back to `Config::ReturnPeriod` with `None`.
+		// Products already sold keep the window they were sold with.
+		#[pallet::weight(<T as Config>::WeightInfo::set_return_period())]
+		pub fn set_return_period(origin: OriginFor<T>, period: Option<MomentOf<T>>) -> DispatchResult {
+			ensure_root(origin)?;
+
+			ensure!(period.map_or(true, |period| !period.is_zero()), Error::<T>::InvalidReturnPeriod);
+			ReturnPolicy::<T>::set(period);
+
+			Self::deposit_event(Event::<T>::ReturnPeriodSet { period });
+			Ok(())
+		}
+
+		// Rule on an open dispute.
+		#[pallet::weight(<T as Config>::WeightInfo::resolve_dispute())]
+		pub fn resolve_dispute(origin: OriginFor<T>, hash: T::Hash, ruling: Ruling) -> DispatchResult {
+			T::ArbiterOrigin::ensure_origin(origin)?;
+
+			let dispute = Disputes::<T>::take(&hash).ok_or(Error::<T>::NoDispute)?;
+			DisputeDeadlines::<T>::mutate_exists(dispute.deadline, |lapsing| {
+				if let Some(hashes) = lapsing {
+					hashes.retain(|lapsing| lapsing != &hash);
+					if hashes.is_empty() {
+						*lapsing = None;
+					}
+				}
+			});
+			Self::settle_dispute(hash, dispute, ruling);
+
+			Ok(())
+		}
+
+		// Record that someone scanned a product, without a signed origin or fees.
+		// Unlike `check_authenticity` this doesn't sell the product, an anonymous caller can't own it.
+		#[pallet::weight(<T as Config>::WeightInfo::record_verification())]
+		pub fn record_verification(origin: OriginFor<T>, hash: T::Hash) -> DispatchResult {
+			ensure_none(origin)?;
+
+			let status = Self::product_status(&hash).ok_or(Error::<T>::UnAuthenticProduct)?;
+
+			let current_block = frame_system::Pallet::<T>::block_number();
+			ensure!(LastVerified::<T>::get(&hash) != Some(current_block), Error::<T>::AlreadyVerified);
+			let verifications = Self::verifications_in_block();
+			ensure!(verifications < T::MaxVerificationsPerBlock::get(), Error::<T>::TooManyVerifications);
+
+			LastVerified::<T>::insert(&hash, current_block);
+			VerificationCount::<T>::mutate(&hash, |count| *count = count.saturating_add(1));
+			VerificationsInBlock::<T>::put((current_block, verifications + 1));
+
+			Self::deposit_event(Event::<T>::ProductVerified { hash, status });
+			Ok(())
+		}
+
+		// Prepay fees of customers' `check_authenticity` and `refund_products` calls.
+		// Only authorised person is allowed to perform this task.
+		#[pallet::weight(<T as Config>::WeightInfo::fund_sponsorship())]
+		pub fn fund_sponsorship(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let all_manufacturer = Manufacturer::<T>::get();
+			let _location = all_manufacturer.binary_search(&who).ok().ok_or(Error::<T>::UnAuthorisedPerson)?;
+
+			// Kept in an account of its own, apart from the manufacturer's deposits.
+			let account = Self::sponsor_account(&who);
+			T::Currency::transfer(&who, &account, amount, ExistenceRequirement::KeepAlive)?;
+
+			Self::deposit_event(Event::<T>::SponsorshipFunded { who, amount });
+			Ok(())
+		}
+
+		// Take back unused sponsorship balance.
+		#[pallet::weight(<T as Config>::WeightInfo::withdraw_sponsorship())]
+		pub fn withdraw_sponsorship(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::sponsorship(&who) >= amount, Error::<T>::InsufficientSponsorship);
+			let account = Self::sponsor_account(&who);
+			T::Currency::transfer(&account, &who, amount, ExistenceRequirement::AllowDeath)?;
+
+			Self::deposit_event(Event::<T>::SponsorshipWithdrawn { who, amount });
+			Ok(())
+		}
+
+		// Set how long the warranty of a product category lasts.
+		#[pallet::weight(<T as Config>::WeightInfo::set_warranty_period())]
+		pub fn set_warranty_period(
+			origin: OriginFor<T>,
+			category: CategoryId,
+			period: MomentOf<T>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			WarrantyPeriod::<T>::insert(category, period);
+
+			Self::deposit_event(Event::<T>::WarrantyPeriodSet { category, period });
+			Ok(())
+		}
+
+		// Put a product in a category.
+		// Only the manufacturer of the product is allowed to perform this task.
+		#[pallet::weight(<T as Config>::WeightInfo::set_product_category())]
+		pub fn set_product_category(
+			origin: OriginFor<T>,
+			hash: T::Hash,
+			category: CategoryId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let manufacturer = ProductManufacturer::<T>::get(&hash).ok_or(Error::<T>::UnAuthenticProduct)?;
+			ensure!(who == manufacturer, Error::<T>::UnAuthorisedPerson);
+
+			ProductCategory::<T>::insert(&hash, category);
+
+			Self::deposit_event(Event::<T>::ProductCategorySet { hash, category });
+			Ok(())
+		}
+
+		// Register the warranty of a product once its sale is final.
+		#[pallet::weight(<T as Config>::WeightInfo::register_warranty())]
+		pub fn register_warranty(origin: OriginFor<T>, hash: T::Hash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			// The sale may be over without anyone having finalized it yet.
+			Self::finalize_if_expired(&hash, &who);
+			ensure!(SellProducts::<T>::contains_key(&hash), Error::<T>::UnsoldProduct);
+			ensure!(ProductOwner::<T>::get(&hash) == Some(who.clone()), Error::<T>::NotProductOwner);
+			ensure!(!Warranties::<T>::contains_key(&hash), Error::<T>::WarrantyAlreadyRegistered);
+
+			let period = WarrantyPeriod::<T>::get(ProductCategory::<T>::get(&hash))
+				.ok_or(Error::<T>::NoWarrantyForCategory)?;
+			let expires = pallet_timestamp::Pallet::<T>::get().saturating_add(period);
+
+			Warranties::<T>::insert(
+				&hash,
+				Warranty { owner: who.clone(), expires, status: WarrantyStatus::Active, claim_reason: None },
+			);
+
+			Self::deposit_event(Event::<T>::WarrantyRegistered { hash, owner: who, expires });
+			Ok(())
+		}
+
+		// File a claim against a registered warranty.
+		#[pallet::weight(<T as Config>::WeightInfo::file_warranty_claim())]
+		pub fn file_warranty_claim(origin: OriginFor<T>, hash: T::Hash, reason: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let reason: CidOf<T> = reason.try_into().map_err(|_| Error::<T>::CidTooLong)?;
+
+			Warranties::<T>::try_mutate(&hash, |maybe_warranty| -> DispatchResult {
+				let warranty = maybe_warranty.as_mut().ok_or(Error::<T>::NoWarranty)?;
+				ensure!(warranty.owner == who, Error::<T>::NotProductOwner);
+				ensure!(
+					pallet_timestamp::Pallet::<T>::get() <= warranty.expires,
+					Error::<T>::WarrantyExpired
+				);
+				ensure!(
+					matches!(warranty.status, WarrantyStatus::Active | WarrantyStatus::Rejected),
+					Error::<T>::InvalidWarrantyStatus
+				);
+
+				warranty.status = WarrantyStatus::Claimed;
+				warranty.claim_reason = Some(reason);
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::<T>::WarrantyClaimFiled { hash, owner: who });
+			Ok(())
+		}
+
+		// Accept a pending warranty claim.
+		// Only the manufacturer of the product is allowed to perform this task.
+		#[pallet::weight(<T as Config>::WeightInfo::accept_warranty_claim())]
+		pub fn accept_warranty_claim(origin: OriginFor<T>, hash: T::Hash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::decide_warranty_claim(&who, &hash, WarrantyStatus::Accepted)?;
+
+			Self::deposit_event(Event::<T>::WarrantyClaimAccepted { hash });
+			Ok(())
+		}
+
+		// Reject a pending warranty claim.
+		// Only the manufacturer of the product is allowed to perform this task.
+		#[pallet::weight(<T as Config>::WeightInfo::reject_warranty_claim())]
+		pub fn reject_warranty_claim(origin: OriginFor<T>, hash: T::Hash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::decide_warranty_claim(&who, &hash, WarrantyStatus::Rejected)?;
+
+			Self::deposit_event(Event::<T>::WarrantyClaimRejected { hash });
+			Ok(())
+		}
+
+		// Make final the sales whose return window is over, for their finalization deposits.
+		// Anyone can call it; products that aren't pending or whose window is still open are skipped.
+		#[pallet::weight(<T as Config>::WeightInfo::finalize_expired(hashes.len() as u32))]
+		pub fn finalize_expired(origin: OriginFor<T>, hashes: Vec<T::Hash>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(hashes.len() as u32 <= MAX_FINALIZE_BATCH, Error::<T>::TooManyProducts);
+
+			let mut finalized = 0u32;
+			for hash in hashes {
+				if Self::finalize_if_expired(&hash, &who) {
+					finalized += 1;
+				}
+			}
+			ensure!(finalized > 0, Error::<T>::NothingToFinalize);
+
+			Ok(())
+		}
+
+		// Answer a status query another chain sent with `Transact`.
+		// The status of the product goes back to the querying chain as an unpaid `Transact` of `response`.
+		#[pallet::weight(<T as Config>::WeightInfo::query_product_status())]
+		pub fn query_product_status(
+			origin: OriginFor<T>,
+			query_id: QueryId,
+			hash: T::Hash,
+			response: ResponseCall,
+		) -> DispatchResult {
+			let querier = T::XcmOrigin::ensure_origin(origin)?;
+			Self::answer_status_query(querier, query_id, hash, response)
+		}
+
+		// Receive the answer to a status query this chain sent to another one.
+		// Only the chain asked can answer, once, about the product asked about.
+		#[pallet::weight(<T as Config>::WeightInfo::product_status_response())]
+		pub fn product_status_response(
+			origin: OriginFor<T>,
+			query_id: QueryId,
+			hash: T::Hash,
+			status: Option<ProductStatus>,
+		) -> DispatchResult {
+			let responder = T::XcmOrigin::ensure_origin(origin)?;
+
+			let (expected, asked) = StatusQueries::<T>::get(query_id).ok_or(Error::<T>::UnknownQuery)?;
+			ensure!(expected == responder && asked == hash, Error::<T>::UnexpectedResponse);
+			StatusQueries::<T>::remove(query_id);
+
+			Self::deposit_event(Event::<T>::ProductStatusReceived { responder, query_id, hash, status });
+			Ok(())
+		}
+
+		// Ask the template pallet of another chain for the status of a product.
+		// `query` names that chain's `query_product_status`. The query goes out as an unpaid
+		// `Transact`, and the answer comes back to `product_status_response`.
+		#[pallet::weight(<T as Config>::WeightInfo::request_product_status())]
+		pub fn request_product_status(
+			origin: OriginFor<T>,
+			dest: Box<MultiLocation>,
+			hash: T::Hash,
+			query: ResponseCall,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let dest = *dest;
+
+			let query_id = NextQueryId::<T>::get();
+			let mut call = sp_std::vec![query.pallet_index, query.call_index];
+			(query_id, hash, Self::response_call()).encode_to(&mut call);
+			let message = Xcm(sp_std::vec![
+				UnpaidExecution { weight_limit: WeightLimit::Unlimited, check_origin: None },
+				Transact {
+					origin_kind: OriginKind::Xcm,
+					require_weight_at_most: query.max_weight,
+					call: call.into(),
+				},
+			]);
+			send_xcm::<T::XcmSender>(dest, message).map_err(|e| {
+				log::warn!(target: "runtime::template", "status query not sent: {:?}", e);
+				Error::<T>::QueryNotSent
+			})?;
+
+			NextQueryId::<T>::put(query_id.wrapping_add(1));
+			StatusQueries::<T>::insert(query_id, (dest, hash));
+
+			Self::deposit_event(Event::<T>::StatusQuerySent { responder: dest, query_id, hash });
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		// Anonymous verifications are only accepted for registered products, at most once per
+		// product per block and up to `MaxVerificationsPerBlock` per block, so the pool can't be
+		// flooded with them.
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::record_verification { hash } = call {
+				if Self::product_status(hash).is_none() {
+					return InvalidTransaction::Call.into()
+				}
+
+				let current_block = frame_system::Pallet::<T>::block_number();
+				if LastVerified::<T>::get(hash) == Some(current_block) {
+					return InvalidTransaction::Stale.into()
+				}
+				if Self::verifications_in_block() >= T::MaxVerificationsPerBlock::get() {
+					return InvalidTransaction::ExhaustsResources.into()
+				}
+
+				ValidTransaction::with_tag_prefix("TemplateVerification")
+					.priority(T::UnsignedPriority::get())
+					.and_provides((hash, current_block))
+					.longevity(3)
+					.propagate(true)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		// Unsigned verifications already recorded in the current block.
+		fn verifications_in_block() -> u32 {
+			let (block, verifications) = VerificationsInBlock::<T>::get();
+			if block == frame_system::Pallet::<T>::block_number() {
+				verifications
+			} else {
+				0
+			}
+		}
+
+		fn do_add_product(who: &T::AccountId, hash: T::Hash) -> DispatchResult {
+			// Sold or returned products keep their manufacturer, so this covers every lifecycle set.
+			ensure!(!ProductManufacturer::<T>::contains_key(&hash), Error::<T>::ProductAlreadyPresent);
+
+			ProductManufacturer::<T>::insert(&hash, who);
+			Self::list_product(who, hash, None);
+
+			Self::deposit_event(Event::ProductAdded { hash, manufacturer: who.clone() });
+			Ok(())
+		}
+
+		// Put `hash` on sale as registered at the current block, moving it out of the block it was
+		// registered at before and of its `from` status in the manufacturer's counters.
+		fn list_product(manufacturer: &T::AccountId, hash: T::Hash, from: Option<ProductStatus>) {
+			let now = frame_system::Pallet::<T>::block_number();
+			if let Some(registered) = RegistrationBlock::<T>::take(&hash) {
+				RegisteredAt::<T>::remove(registered, &hash);
+			}
+			ProductsHash::<T>::insert(&hash, now);
+			RegisteredAt::<T>::insert(now, &hash, ());
+			RegistrationBlock::<T>::insert(&hash, now);
+			ManufacturerProducts::<T>::insert(manufacturer, &hash, ());
+			ManufacturerCounts::<T>::mutate(manufacturer, |counts| counts.transition(from, ProductStatus::Registered));
+		}
+
+		// Sell a registered product to `who` and open its return window.
+		fn do_sell(who: T::AccountId, hash: T::Hash) -> DispatchResult {
 			// Check the product in fresh added products.
-			let mut all_products = ProductsHash::<T>::get();
-			let location = all_products.binary_search(&hash).ok().ok_or(Error::<T>::UnAuthenticProduct)?;
+			ensure!(ProductsHash::<T>::contains_key(&hash), Error::<T>::UnAuthenticProduct);
 
+			// After the return period the users are not able to return the product.
+			// The window is fixed at sale time, later policy changes don't move it.
+			let refund_invalid = pallet_timestamp::Pallet::<T>::get().saturating_add(Self::return_period());
 
 			// Now the product is sell for first time.
 			// Add in the partial sell product..because it might me return in future.
-			let mut partial_sell = PartialSellProduct::<T>::get();
-			partial_sell.push(hash);
-			PartialSellProduct::<T>::put(partial_sell);
+			PartialSellProduct::<T>::insert(&hash, refund_invalid);
+			// The seller backs the finalization, so customers without funds, sponsored or
+			// unsigned, can still buy. The sale is final once the window is over either way.
+			let deposit = T::FinalizeDeposit::get();
+			if let Some(maker) = ProductManufacturer::<T>::get(&hash).filter(|_| !deposit.is_zero()) {
+				if T::Currency::reserve(&maker, deposit).is_ok() {
+					FinalizeDeposits::<T>::insert(&hash, (&maker, deposit));
+				}
+			}
+			ProductOwner::<T>::insert(&hash, &who);
 
-			// After 15 days the users are not able to return the product.
-			let expire_time = T::expire_time::get();  // (60/6) * 60 * 24 * 15
-			// Record the product with the current BlockNumber
-			let refund_invalid = frame_system::Pallet::<T>::block_number() + expire_time.into();
+			// Remove this item from freshly added products.
+			ProductsHash::<T>::remove(&hash);
+			Self::count_transition(&hash, Some(ProductStatus::Registered), ProductStatus::PendingReturn);
 
-			PartialProductTime::<T>::insert(refund_invalid, &hash);
+			Self::deposit_event(Event::<T>::AuthenticProduct { hash, who });
 
-			// Remove this item from freshly added products.
-			all_products.remove(location);
-			ProductsHash::<T>::put(all_products);
+			Ok(())
+		}
+
+		// Fetch the catalogue of every manufacturer whose key is in the local keystore and
+		// submit the products that are not registered yet.
+		fn sync_catalogues(block_number: BlockNumberFor<T>) -> Result<(), &'static str> {
+			// Only sync once every `CatalogueSyncInterval` blocks.
+			let last_sync = StorageValueRef::persistent(b"pallet-template::last-catalogue-sync");
+			let res = last_sync.mutate(
+				|last: Result<Option<BlockNumberFor<T>>, StorageRetrievalError>| match last {
+					Ok(Some(block)) if block_number < block + T::CatalogueSyncInterval::get() =>
+						Err(()),
+					_ => Ok(block_number),
+				},
+			);
+			match res {
+				Ok(_) => {},
+				Err(MutateStorageError::ValueFunctionFailed(_)) => return Ok(()),
+				Err(MutateStorageError::ConcurrentModification(_)) =>
+					return Err("another worker is syncing catalogues"),
+			}
+
+			let manufacturers = Manufacturer::<T>::get();
+			for key in <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all() {
+				let generic: <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic =
+					key.into();
+				let public: T::Public = generic.into();
+				let who = public.clone().into_account();
 
-			Self::deposit_event(Event::<T>::AuthenticProduct);
+				if manufacturers.binary_search(&who).is_err() {
+					continue
+				}
+				let endpoint = match CatalogueEndpoint::<T>::get(&who) {
+					Some(endpoint) => endpoint,
+					None => continue,
+				};
+				// A broken catalogue only holds back its own manufacturer.
+				let url = match sp_std::str::from_utf8(&endpoint) {
+					Ok(url) => url,
+					Err(_) => {
+						log::warn!(target: "runtime::template", "catalogue endpoint of {:?} is not utf-8", who);
+						continue
+					},
+				};
+				let catalogue = match Self::fetch_catalogue(url) {
+					Ok(catalogue) => catalogue,
+					Err(e) => {
+						log::warn!(target: "runtime::template", "failed to fetch catalogue of {:?}: {:?}", who, e);
+						continue
+					},
+				};
+
+				let mut hashes: Vec<T::Hash> = catalogue
+					.into_iter()
+					.filter(|hash| !ProductManufacturer::<T>::contains_key(hash))
+					.collect();
+				hashes.sort();
+				hashes.dedup();
+
+				for batch in hashes.chunks(T::MaxCatalogueBatch::get().max(1) as usize) {
+					let results = Signer::<T, T::AuthorityId>::all_accounts()
+						.with_filter(sp_std::vec![public.clone()])
+						.send_signed_transaction(|_| Call::add_products { hashes: batch.to_vec() });
+					for (_, result) in results {
+						if result.is_err() {
+							log::warn!(target: "runtime::template", "failed to submit catalogue batch of {:?}", who);
+						}
+					}
+				}
+			}
 
 			Ok(())
 		}
 
-		#[pallet::weight(10_000)]
-		pub fn refund_products(who: OriginFor<T>, hash: T::Hash) -> DispatchResult {
-			ensure_signed(who.clone())?;
+		// Download a catalogue: one hex encoded product hash per line.
+		fn fetch_catalogue(url: &str) -> Result<Vec<T::Hash>, http::Error> {
+			let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(2_000));
+			let pending = http::Request::get(url)
+				.deadline(deadline)
+				.send()
+				.map_err(|_| http::Error::IoError)?;
+			let response = pending.try_wait(deadline).map_err(|_| http::Error::DeadlineReached)??;
+			if response.code != 200 {
+				log::warn!(target: "runtime::template", "unexpected catalogue status code: {}", response.code);
+				return Err(http::Error::Unknown)
+			}
 
-			let mut products = PartialSellProduct::<T>::get();
-			// check this product is sold or not?
-			let location = products.binary_search(&hash).ok().ok_or(Error::<T>::UnsoldProduct)?;
+			let body = response.body().collect::<Vec<u8>>();
+			let body = sp_std::str::from_utf8(&body).map_err(|_| http::Error::Unknown)?;
 
-			// Now remove the product from partial sale.
-			products.remove(location);
-			PartialSellProduct::<T>::put(products);
+			let mut skipped = 0u32;
+			let hashes = body
+				.lines()
+				.filter(|line| !line.trim().is_empty())
+				.filter_map(|line| {
+					let hash = Self::parse_hash(line);
+					skipped += hash.is_none() as u32;
+					hash
+				})
+				.collect();
+			if skipped > 0 {
+				log::warn!(target: "runtime::template", "skipped {} catalogue lines that are not product hashes", skipped);
+			}
 
-			// Add the product into returned product storage for originality check in manufacturing unit and then again this product is available for sale.
-			let mut items = ReturnedProducts::<T>::get();
-			items.push(hash);
-			ReturnedProducts::<T>::put(items);
+			Ok(hashes)
+		}
+
+		fn parse_hash(line: &str) -> Option<T::Hash> {
+			let hex = line.trim();
+			let hex = hex.strip_prefix("0x").unwrap_or(hex);
+			if hex.is_empty() || !hex.is_ascii() || hex.len() % 2 != 0 {
+				return None
+			}
+			let bytes = (0..hex.len())
+				.step_by(2)
+				.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+				.collect::<Option<Vec<u8>>>()?;
+
+			T::Hash::decode_all(&mut &bytes[..]).ok()
+		}
+
+		/// Account holding the sponsorship `manufacturer` prepaid fees with.
+		pub fn sponsor_account(manufacturer: &T::AccountId) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(manufacturer)
+		}
 
+		/// Sponsorship balance left to `manufacturer`.
+		pub fn sponsorship(manufacturer: &T::AccountId) -> BalanceOf<T> {
+			T::Currency::free_balance(&Self::sponsor_account(manufacturer))
+		}
+
+		/// Return window given to new sales: the root policy if set, `Config::ReturnPeriod`
+		/// otherwise.
+		pub fn return_period() -> MomentOf<T> {
+			ReturnPolicy::<T>::get().unwrap_or_else(T::ReturnPeriod::get)
+		}
+
+		// Send the status of `hash` back to `querier` as a `Transact` of `response`.
+		pub(crate) fn answer_status_query(
+			querier: MultiLocation,
+			query_id: QueryId,
+			hash: T::Hash,
+			response: ResponseCall,
+		) -> DispatchResult {
+			ensure!(
+				response.max_weight.all_lte(T::MaxResponseWeight::get()),
+				Error::<T>::ResponseTooHeavy
+			);
+			let status = Self::product_status(&hash);
+
+			let mut call = sp_std::vec![response.pallet_index, response.call_index];
+			(query_id, hash, status).encode_to(&mut call);
+			let message = Xcm(sp_std::vec![
+				UnpaidExecution { weight_limit: WeightLimit::Unlimited, check_origin: None },
+				Transact {
+					origin_kind: OriginKind::Xcm,
+					require_weight_at_most: response.max_weight,
+					call: call.into(),
+				},
+			]);
+			send_xcm::<T::XcmSender>(querier, message).map_err(|e| {
+				log::warn!(target: "runtime::template", "status query answer not sent: {:?}", e);
+				Error::<T>::ResponseNotSent
+			})?;
+
+			Self::deposit_event(Event::<T>::ProductStatusQueried { querier, query_id, hash, status });
 			Ok(())
 		}
 
+		// `product_status_response` of this pallet, where the answers to queries sent from here go.
+		fn response_call() -> ResponseCall {
+			let call = Call::<T>::product_status_response { query_id: 0, hash: Default::default(), status: None };
+			ResponseCall {
+				pallet_index: <Self as PalletInfoAccess>::index() as u8,
+				call_index: call.encode()[0],
+				max_weight: T::MaxResponseWeight::get(),
+			}
+		}
+
+		// Make the sale of `hash` final if its return window is over, paying its finalization
+		// deposit to `to`. Returns whether it did.
+		pub(crate) fn finalize_if_expired(hash: &T::Hash, to: &T::AccountId) -> bool {
+			let expired = PartialSellProduct::<T>::get(hash)
+				.map_or(false, |deadline| deadline <= pallet_timestamp::Pallet::<T>::get());
+			if !expired {
+				return false
+			}
+
+			PartialSellProduct::<T>::remove(hash);
+			SellProducts::<T>::insert(hash, frame_system::Pallet::<T>::block_number());
+			Self::count_transition(hash, Some(ProductStatus::PendingReturn), ProductStatus::Sold);
+			if let Some((depositor, deposit)) = FinalizeDeposits::<T>::take(hash) {
+				if &depositor == to {
+					T::Currency::unreserve(&depositor, deposit);
+				} else {
+					let _ = T::Currency::repatriate_reserved(&depositor, to, deposit, BalanceStatus::Free);
+				}
+			}
+
+			Self::deposit_event(Event::<T>::SaleFinalized { hash: *hash });
+			true
+		}
+
+		/// Lifecycle status of a product, `None` if it was never registered.
+		pub fn product_status(hash: &T::Hash) -> Option<ProductStatus> {
+			match PartialSellProduct::<T>::get(hash) {
+				// The return window is over, the sale is final even if nobody finalized it yet.
+				Some(deadline) if deadline <= pallet_timestamp::Pallet::<T>::get() => Some(ProductStatus::Sold),
+				_ => Self::stored_status(hash),
+			}
+		}
+
+		/// Lifecycle set `hash` is stored in, `PendingReturn` until its sale is finalized.
+		pub(crate) fn stored_status(hash: &T::Hash) -> Option<ProductStatus> {
+			if ProductsHash::<T>::contains_key(hash) {
+				Some(ProductStatus::Registered)
+			} else if PartialSellProduct::<T>::contains_key(hash) {
+				Some(ProductStatus::PendingReturn)
+			} else if ReturnedProducts::<T>::contains_key(hash) {
+				Some(ProductStatus::Returned)
+			} else if SellProducts::<T>::contains_key(hash) {
+				Some(ProductStatus::Sold)
+			} else {
+				None
+			}
+		}
+
+		/// Products with `status`, starting after `cursor`.
+		pub fn products_by_status(
+			status: ProductStatus,
+			cursor: Option<T::Hash>,
+			limit: u32,
+		) -> ProductPage<T::Hash, T::Hash> {
+			let keys = match status {
+				ProductStatus::Registered => Self::keys_after::<ProductsHash<T>, _>(cursor),
+				ProductStatus::Returned => Self::keys_after::<ReturnedProducts<T>, _>(cursor),
+				ProductStatus::PendingReturn => {
+					let open = Self::pending_after(cursor).map(|(hash, expired)| (hash, (!expired).then_some(hash)));
+					return Self::page(open, limit)
+				},
+				// Sales whose window is over but that nobody finalized yet come after the final ones.
+				ProductStatus::Sold => {
+					let expired = |cursor| {
+						Self::pending_after(cursor).map(|(hash, expired)| (hash, expired.then_some(hash)))
+					};
+					return match cursor {
+						Some(hash) if PartialSellProduct::<T>::contains_key(hash) => Self::page(expired(cursor), limit),
+						_ => {
+							let sold = Self::keys_after::<SellProducts<T>, _>(cursor).map(|hash| (hash, Some(hash)));
+							Self::page(sold.chain(expired(None)), limit)
+						},
+					}
+				},
+			};
+
+			Self::page(keys.map(|hash| (hash, Some(hash))), limit)
+		}
+
+		/// Products registered by `manufacturer`, starting after `cursor`.
+		pub fn products_by_manufacturer(
+			manufacturer: T::AccountId,
+			cursor: Option<T::Hash>,
+			limit: u32,
+		) -> ProductPage<T::Hash, T::Hash> {
+			let hashes = match cursor {
+				Some(cursor) => ManufacturerProducts::<T>::iter_key_prefix_from(
+					&manufacturer,
+					ManufacturerProducts::<T>::hashed_key_for(&manufacturer, cursor),
+				),
+				None => ManufacturerProducts::<T>::iter_key_prefix(&manufacturer),
+			};
+
+			Self::page(hashes.map(|hash| (hash, Some(hash))), limit)
+		}
+
+		/// Number of `manufacturer`'s products in each lifecycle status. Sales whose return window
+		/// is over count as sold before anyone finalizes them, so this reads every pending sale.
+		pub fn manufacturer_counts(manufacturer: &T::AccountId) -> ProductCounts {
+			let mut counts = ManufacturerCounts::<T>::get(manufacturer);
+			for (hash, expired) in Self::pending_after(None) {
+				if expired && ProductManufacturer::<T>::get(&hash).as_ref() == Some(manufacturer) {
+					counts.transition(Some(ProductStatus::PendingReturn), ProductStatus::Sold);
+				}
+			}
+			counts
+		}
+
+		/// Products registered between blocks `from` and `to` inclusive, ordered by block, starting
+		/// after `cursor`.
+		pub fn products_registered_between(
+			from: BlockNumberFor<T>,
+			to: BlockNumberFor<T>,
+			cursor: Option<RangeCursor<BlockNumberFor<T>, T::Hash>>,
+			limit: u32,
+		) -> ProductPage<T::Hash, RangeCursor<BlockNumberFor<T>, T::Hash>> {
+			let first = match cursor {
+				Some((block, Some(_))) => block,
+				Some((block, None)) if block >= to =>
+					return ProductPage { products: Vec::new(), next: None },
+				Some((block, None)) => block + One::one(),
+				None => from,
+			};
+			let resume = cursor.and_then(|(block, hash)| Some((block, hash?)));
+
+			// Every block read counts against the page's budget, so empty ranges can't make a page
+			// read more than `MaxPageSize` entries.
+			let blocks = sp_std::iter::successors(Some(first.max(from)), move |block| {
+				(*block < to).then(|| *block + One::one())
+			})
+			.take_while(move |block| *block <= to);
+			let entries = blocks.flat_map(move |block| {
+				let hashes = match resume {
+					Some((at, hash)) if at == block => RegisteredAt::<T>::iter_key_prefix_from(
+						block,
+						RegisteredAt::<T>::hashed_key_for(block, hash),
+					),
+					_ => RegisteredAt::<T>::iter_key_prefix(block),
+				};
+				hashes
+					.map(move |hash| ((block, Some(hash)), Some(hash)))
+					.chain(sp_std::iter::once(((block, None), None)))
+			});
+
+			Self::page(entries, limit)
+		}
+
+		// Move a product between the counters of its manufacturer.
+		fn count_transition(hash: &T::Hash, from: Option<ProductStatus>, to: ProductStatus) {
+			if let Some(manufacturer) = ProductManufacturer::<T>::get(hash) {
+				ManufacturerCounts::<T>::mutate(manufacturer, |counts| counts.transition(from, to));
+			}
+		}
+
+		// Keys of `M` after `cursor`, from the start without a cursor.
+		fn keys_after<M, V>(cursor: Option<T::Hash>) -> M::KeyIterator
+		where
+			M: frame_support::storage::IterableStorageMap<T::Hash, V>
+				+ frame_support::storage::StorageMap<T::Hash, V>,
+			V: FullCodec,
+		{
+			match cursor {
+				Some(cursor) => M::iter_keys_from(M::hashed_key_for(cursor)),
+				None => M::iter_keys(),
+			}
+		}
+
+		// Pending sales after `cursor`, and whether their return window is over.
+		fn pending_after(cursor: Option<T::Hash>) -> impl Iterator<Item = (T::Hash, bool)> {
+			let now = pallet_timestamp::Pallet::<T>::get();
+			let pending = match cursor {
+				Some(cursor) => PartialSellProduct::<T>::iter_from(PartialSellProduct::<T>::hashed_key_for(cursor)),
+				None => PartialSellProduct::<T>::iter(),
+			};
+			pending.map(move |(hash, deadline)| (hash, deadline <= now))
+		}
+
+		// Collect a page out of `entries`: their cursor and the product, `None` if the entry doesn't
+		// match the listing. Stops after `limit` products or `MaxPageSize` entries.
+		fn page<C>(
+			entries: impl Iterator<Item = (C, Option<T::Hash>)>,
+			limit: u32,
+		) -> ProductPage<T::Hash, C> {
+			let max = T::MaxPageSize::get().max(1);
+			let limit = limit.clamp(1, max) as usize;
+
+			let mut entries = entries.peekable();
+			let mut products = Vec::new();
+			let mut read = 0;
+			while let Some((cursor, product)) = entries.next() {
+				products.extend(product);
+				read += 1;
+				if products.len() >= limit || read >= max {
+					let next = entries.peek().is_some().then_some(cursor);
+					return ProductPage { products, next }
+				}
+			}
+
+			ProductPage { products, next: None }
+		}
+
+		// Close a pending warranty claim on behalf of the product's manufacturer.
+		fn decide_warranty_claim(
+			who: &T::AccountId,
+			hash: &T::Hash,
+			status: WarrantyStatus,
+		) -> DispatchResult {
+			let manufacturer = ProductManufacturer::<T>::get(hash).ok_or(Error::<T>::UnAuthenticProduct)?;
+			ensure!(who == &manufacturer, Error::<T>::UnAuthorisedPerson);
+
+			Warranties::<T>::try_mutate(hash, |maybe_warranty| -> DispatchResult {
+				let warranty = maybe_warranty.as_mut().ok_or(Error::<T>::NoWarranty)?;
+				ensure!(warranty.status == WarrantyStatus::Claimed, Error::<T>::InvalidWarrantyStatus);
+
+				warranty.status = status;
+				Ok(())
+			})
+		}
+
+		// Move the deposit and the product according to the ruling.
+		// The dispute must already be removed from `Disputes`.
+		fn settle_dispute(
+			hash: T::Hash,
+			dispute: DisputeOf<T>,
+			ruling: Ruling,
+		) {
+			let winner = match ruling {
+				Ruling::Customer => &dispute.customer,
+				Ruling::Manufacturer => &dispute.manufacturer,
+			};
+
+			// The opener gets the deposit back if they were right, otherwise it goes to the other side.
+			if &dispute.opener == winner {
+				T::Currency::unreserve(&dispute.opener, dispute.deposit);
+			} else {
+				let _ = T::Currency::repatriate_reserved(
+					&dispute.opener,
+					winner,
+					dispute.deposit,
+					BalanceStatus::Free,
+				);
+			}
+
+			ReturnedProducts::<T>::remove(&hash);
+			ReturnedBy::<T>::remove(&hash);
+
+			match ruling {
+				// Refund confirmed, product is available for sale again.
+				Ruling::Customer => {
+					ProductOwner::<T>::remove(&hash);
+					Self::list_product(&dispute.manufacturer, hash, Some(ProductStatus::Returned));
+				},
+				// Refund rejected, the sale is final.
+				Ruling::Manufacturer => {
+					SellProducts::<T>::insert(&hash, frame_system::Pallet::<T>::block_number());
+					Self::count_transition(&hash, Some(ProductStatus::Returned), ProductStatus::Sold);
+				},
+			}
+
+			Self::deposit_event(Event::<T>::DisputeResolved { hash, ruling });
+		}
+
+		// Invariants of the product lifecycle, checked by `try-runtime` after every block.
+		#[cfg(any(feature = "try-runtime", test))]
+		pub(crate) fn do_try_state(_n: BlockNumberFor<T>) -> Result<(), &'static str> {
+			let manufacturers = Manufacturer::<T>::get();
+			ensure!(
+				manufacturers.windows(2).all(|pair| pair[0] < pair[1]),
+				"manufacturer list is not sorted or has duplicates"
+			);
+
+			// Every product is in exactly one lifecycle set.
+			let mut products = 0usize;
+			for hash in ProductsHash::<T>::iter_keys()
+				.chain(PartialSellProduct::<T>::iter_keys())
+				.chain(SellProducts::<T>::iter_keys())
+				.chain(ReturnedProducts::<T>::iter_keys())
+			{
+				let sets = [
+					ProductsHash::<T>::contains_key(&hash),
+					PartialSellProduct::<T>::contains_key(&hash),
+					SellProducts::<T>::contains_key(&hash),
+					ReturnedProducts::<T>::contains_key(&hash),
+				];
+				ensure!(sets.iter().filter(|in_set| **in_set).count() == 1, "product is in several lifecycle sets");
+
+				let manufacturer = ProductManufacturer::<T>::get(&hash).ok_or("product has no manufacturer")?;
+				ensure!(manufacturers.binary_search(&manufacturer).is_ok(), "product manufacturer is unknown");
+				products += 1;
+			}
+			ensure!(
+				ProductManufacturer::<T>::iter_keys().count() == products,
+				"registered product is in no lifecycle set"
+			);
+
+			// Every product is indexed under exactly one registration block, the one it is for sale at.
+			let mut registered = 0usize;
+			for (block, hash, ()) in RegisteredAt::<T>::iter() {
+				ensure!(ProductManufacturer::<T>::contains_key(&hash), "registration of an unknown product");
+				ensure!(
+					RegistrationBlock::<T>::get(&hash) == Some(block),
+					"product registration block is not indexed both ways"
+				);
+				registered += 1;
+			}
+			ensure!(registered == products, "product registration block is not indexed once");
+			ensure!(
+				RegistrationBlock::<T>::iter_keys().count() == products,
+				"registration block of an unknown product"
+			);
+			for (hash, block) in ProductsHash::<T>::iter() {
+				ensure!(
+					RegistrationBlock::<T>::get(&hash) == Some(block),
+					"product for sale is not indexed at its registration block"
+				);
+			}
+
+			// The manufacturer index and counters match the lifecycle sets.
+			let mut counts: sp_std::collections::btree_map::BTreeMap<T::AccountId, ProductCounts> =
+				Default::default();
+			for (hash, manufacturer) in ProductManufacturer::<T>::iter() {
+				ensure!(
+					ManufacturerProducts::<T>::contains_key(&manufacturer, &hash),
+					"product missing from its manufacturer's index"
+				);
+				let status = Self::stored_status(&hash).ok_or("product has no status")?;
+				counts.entry(manufacturer).or_default().transition(None, status);
+			}
+			ensure!(
+				ManufacturerProducts::<T>::iter_keys().count() == products,
+				"manufacturer index has unknown products"
+			);
+			for (manufacturer, stored) in ManufacturerCounts::<T>::iter() {
+				ensure!(
+					counts.remove(&manufacturer).unwrap_or_default() == stored,
+					"manufacturer counters are out of sync"
+				);
+			}
+			ensure!(counts.is_empty(), "manufacturer has products but no counters");
+
+			// A commitment is only kept until its product is sold.
+			for hash in CommittedProducts::<T>::iter_keys() {
+				ensure!(ProductsHash::<T>::contains_key(&hash), "committed product is not for sale");
+			}
+
+			// Finalization deposits are only held for pending sales.
+			for hash in FinalizeDeposits::<T>::iter_keys() {
+				ensure!(PartialSellProduct::<T>::contains_key(&hash), "finalization deposit of a settled sale");
+			}
+
+			Ok(())
+		}
 	}
 }
 
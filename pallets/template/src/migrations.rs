@@ -0,0 +1,826 @@
+//! Storage migrations of the template pallet.
+//!
+//! Every migration is versioned against the pallet's `StorageVersion` and is a no-op once the
+//! on-chain version has moved past it, so it is safe to leave in the runtime's `Migrations`
+//! tuple for a release or two.
+
+use crate::{Config, Pallet};
+use frame_support::{
+	log,
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use sp_runtime::SaturatedConversion;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Expected block time in milliseconds, twice the minimum period `pallet_timestamp` enforces.
+fn block_time<T: Config>() -> u64 {
+	let minimum_period = <T as pallet_timestamp::Config>::MinimumPeriod::get();
+	minimum_period.saturated_into::<u64>().saturating_mul(2).max(1)
+}
+
+/// Storage layout before version 1, when every lifecycle set was a single `Vec` value.
+pub mod v0 {
+	use crate::{Config, Pallet};
+	use frame_support::{pallet_prelude::*, storage_alias};
+	use frame_system::pallet_prelude::BlockNumberFor;
+	use sp_std::vec::Vec;
+
+	#[storage_alias]
+	pub type Manufacturer<T: Config> =
+		StorageValue<Pallet<T>, Vec<<T as frame_system::Config>::AccountId>, ValueQuery>;
+
+	#[storage_alias]
+	pub type ProductsHash<T: Config> = StorageValue<Pallet<T>, Vec<<T as frame_system::Config>::Hash>, ValueQuery>;
+
+	#[storage_alias]
+	pub type PartialSellProduct<T: Config> =
+		StorageValue<Pallet<T>, Vec<<T as frame_system::Config>::Hash>, ValueQuery>;
+
+	#[storage_alias]
+	pub type SellProducts<T: Config> = StorageValue<Pallet<T>, Vec<<T as frame_system::Config>::Hash>, ValueQuery>;
+
+	#[storage_alias]
+	pub type ReturnedProducts<T: Config> =
+		StorageValue<Pallet<T>, Vec<<T as frame_system::Config>::Hash>, ValueQuery>;
+
+	/// Only one product per expiry block was kept, later sales in the same block overwrote it.
+	#[storage_alias]
+	pub type PartialProductTime<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		BlockNumberFor<T>,
+		<T as frame_system::Config>::Hash,
+		ValueQuery,
+	>;
+}
+
+pub mod v1 {
+	use super::*;
+	use crate::{ProductManufacturer, ProductsHash, ReturnedProducts, SellProducts};
+	use sp_std::collections::btree_map::BTreeMap;
+	use v3::{PartialProductTime, PartialSellProduct};
+
+	/// Moves the lifecycle sets from `Vec` values to maps keyed by product hash, and
+	/// `PartialProductTime` to a double map so several sales can share an expiry block.
+	///
+	/// Products in the return window whose expiry was lost to the old single-entry index get a
+	/// fresh window starting at the upgrade block.
+	///
+	/// Products had no manufacturer of record before version 1, so every product is attributed
+	/// to the first manufacturer in the list, the only one on a single-manufacturer chain.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain = Pallet::<T>::on_chain_storage_version();
+			if onchain != 0 {
+				log::info!(
+					target: "runtime::template",
+					"skipping v1 migration, on-chain storage version is {:?}",
+					onchain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			// Return windows are counted in blocks until version 4.
+			let return_period = v3::ReturnPolicy::<T>::get().unwrap_or_else(|| {
+				(T::ReturnPeriod::get().saturated_into::<u64>() / block_time::<T>()).saturated_into()
+			});
+
+			// The old keys are prefixes of the new ones, so clear them before writing anything.
+			let expiries: BTreeMap<T::Hash, _> =
+				v0::PartialProductTime::<T>::drain().map(|(block, hash)| (hash, block)).collect();
+			let registered = v0::ProductsHash::<T>::take();
+			let partial = v0::PartialSellProduct::<T>::take();
+			let sold = v0::SellProducts::<T>::take();
+			let returned = v0::ReturnedProducts::<T>::take();
+
+			let manufacturers = v0::Manufacturer::<T>::get();
+			let products = registered.len() + partial.len() + sold.len() + returned.len();
+			match manufacturers.first() {
+				Some(maker) => {
+					if manufacturers.len() > 1 {
+						log::warn!(
+							target: "runtime::template",
+							"attributing {} products to the first of {} manufacturers",
+							products,
+							manufacturers.len(),
+						);
+					}
+					for hash in registered.iter().chain(&partial).chain(&sold).chain(&returned) {
+						ProductManufacturer::<T>::insert(hash, maker);
+					}
+				},
+				None if products > 0 => {
+					log::warn!(
+						target: "runtime::template",
+						"no manufacturer to attribute {} products to",
+						products,
+					);
+				},
+				None => {},
+			}
+
+			let mut writes = 5 + expiries.len() as u64;
+			for hash in registered.iter() {
+				ProductsHash::<T>::insert(hash, now);
+			}
+			for hash in sold.iter() {
+				SellProducts::<T>::insert(hash, now);
+			}
+			for hash in returned.iter() {
+				ReturnedProducts::<T>::insert(hash, now);
+			}
+			for hash in partial.iter() {
+				match expiries.get(hash) {
+					Some(expiry) if *expiry <= now => {
+						SellProducts::<T>::insert(hash, now);
+					},
+					expiry => {
+						let expiry = expiry.copied().unwrap_or_else(|| now + return_period);
+						PartialSellProduct::<T>::insert(hash, expiry);
+						PartialProductTime::<T>::insert(expiry, hash, ());
+						writes += 1;
+					},
+				}
+			}
+			// Each product is written to its lifecycle set and attributed to a manufacturer.
+			writes += 2 * products as u64;
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::template",
+				"migrated {} products to storage version 1",
+				products,
+			);
+
+			T::DbWeight::get().reads_writes(8 + expiries.len() as u64, writes + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			use codec::Encode;
+			use sp_std::collections::btree_set::BTreeSet;
+
+			let distinct = |hashes: Vec<T::Hash>| hashes.into_iter().collect::<BTreeSet<_>>().len() as u32;
+			let registered = distinct(v0::ProductsHash::<T>::get());
+			let partial = distinct(v0::PartialSellProduct::<T>::get());
+			let sold = distinct(v0::SellProducts::<T>::get());
+			let returned = distinct(v0::ReturnedProducts::<T>::get());
+			let attributed = !v0::Manufacturer::<T>::get().is_empty();
+
+			Ok((registered, partial, sold, returned, attributed).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+			use frame_support::ensure;
+
+			let (registered, partial, sold, returned, attributed): (u32, u32, u32, u32, bool) =
+				Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 1, "storage version not updated");
+			ensure!(ProductsHash::<T>::iter_keys().count() as u32 == registered, "registered products lost");
+			ensure!(ReturnedProducts::<T>::iter_keys().count() as u32 == returned, "returned products lost");
+
+			// Sales whose return window had already closed move from the partial set to the sold one.
+			let pending = PartialSellProduct::<T>::iter_keys().count() as u32;
+			let final_sales = SellProducts::<T>::iter_keys().count() as u32;
+			ensure!(pending <= partial, "too many pending sales");
+			ensure!(final_sales >= sold, "sold products lost");
+			ensure!(pending + final_sales >= partial, "pending sales lost");
+
+			for (hash, expiry) in PartialSellProduct::<T>::iter() {
+				ensure!(PartialProductTime::<T>::contains_key(expiry, hash), "pending sale without expiry");
+			}
+
+			if attributed {
+				let products = ProductManufacturer::<T>::iter_keys().count() as u32;
+				ensure!(products == registered + pending + final_sales + returned, "product without manufacturer");
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v2 {
+	use super::*;
+	use crate::{ProductManufacturer, RegisteredAt};
+
+	/// Indexes every product by its registration block.
+	///
+	/// The registration block of products registered before the upgrade isn't stored anywhere, so
+	/// they are indexed at the upgrade block.
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain = Pallet::<T>::on_chain_storage_version();
+			if onchain != 1 {
+				log::info!(
+					target: "runtime::template",
+					"skipping v2 migration, on-chain storage version is {:?}",
+					onchain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let mut products = 0u64;
+			for hash in ProductManufacturer::<T>::iter_keys() {
+				RegisteredAt::<T>::insert(now, hash, ());
+				products += 1;
+			}
+
+			StorageVersion::new(2).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::template",
+				"indexed the registration block of {} products",
+				products,
+			);
+
+			T::DbWeight::get().reads_writes(1 + products, products + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			use codec::Encode;
+
+			Ok((ProductManufacturer::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+			use frame_support::ensure;
+
+			let products: u32 = Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 2, "storage version not updated");
+			ensure!(RegisteredAt::<T>::iter_keys().count() as u32 == products, "products not indexed");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v3 {
+	use super::*;
+	use crate::{CategoryId, ManufacturerCounts, ManufacturerProducts, ProductCounts, ProductManufacturer, Warranty};
+	use frame_support::{
+		pallet_prelude::{Blake2_128Concat, StorageDoubleMap, StorageMap, StorageValue},
+		storage_alias,
+	};
+	use frame_system::pallet_prelude::BlockNumberFor;
+	use sp_std::collections::btree_map::BTreeMap;
+
+	/// Sold products inside the return window, with the block the window closes at. Return
+	/// windows and warranties were counted in blocks until version 4.
+	#[storage_alias]
+	pub type PartialSellProduct<T: Config> =
+		StorageMap<Pallet<T>, Blake2_128Concat, <T as frame_system::Config>::Hash, BlockNumberFor<T>>;
+
+	#[storage_alias]
+	pub type PartialProductTime<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		BlockNumberFor<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::Hash,
+		(),
+	>;
+
+	#[storage_alias]
+	pub type ReturnPolicy<T: Config> = StorageValue<Pallet<T>, BlockNumberFor<T>>;
+
+	#[storage_alias]
+	pub type WarrantyPeriod<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, CategoryId, BlockNumberFor<T>>;
+
+	#[storage_alias]
+	pub type Warranties<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::Hash,
+		Warranty<<T as frame_system::Config>::AccountId, BlockNumberFor<T>>,
+	>;
+
+	/// Builds the per-manufacturer product index and lifecycle counters.
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain = Pallet::<T>::on_chain_storage_version();
+			if onchain != 2 {
+				log::info!(
+					target: "runtime::template",
+					"skipping v3 migration, on-chain storage version is {:?}",
+					onchain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut counts: BTreeMap<T::AccountId, ProductCounts> = BTreeMap::new();
+			let mut products = 0u64;
+			for (hash, manufacturer) in ProductManufacturer::<T>::iter() {
+				ManufacturerProducts::<T>::insert(&manufacturer, hash, ());
+				if let Some(status) = Pallet::<T>::stored_status(&hash) {
+					counts.entry(manufacturer).or_default().transition(None, status);
+				}
+				products += 1;
+			}
+			let manufacturers = counts.len() as u64;
+			for (manufacturer, counts) in counts {
+				ManufacturerCounts::<T>::insert(manufacturer, counts);
+			}
+
+			StorageVersion::new(3).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::template",
+				"indexed {} products of {} manufacturers",
+				products,
+				manufacturers,
+			);
+
+			// Finding a product's status reads up to four lifecycle sets.
+			T::DbWeight::get().reads_writes(1 + 5 * products, products + manufacturers + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			use codec::Encode;
+
+			Ok((ProductManufacturer::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+			use frame_support::ensure;
+
+			let products: u32 = Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 3, "storage version not updated");
+			ensure!(ManufacturerProducts::<T>::iter_keys().count() as u32 == products, "products not indexed");
+			let counted: u32 = ManufacturerCounts::<T>::iter_values()
+				.map(|counts| counts.registered + counts.pending + counts.sold + counts.returned)
+				.sum();
+			ensure!(counted == products, "products not counted");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v4 {
+	use super::*;
+	use crate::{MomentOf, PartialSellProduct, ReturnPolicy, Warranties, Warranty, WarrantyPeriod};
+	use frame_support::{
+		pallet_prelude::{Blake2_128Concat, BoundedVec, StorageDoubleMap, StorageValue, Twox64Concat},
+		storage_alias,
+	};
+	use frame_system::pallet_prelude::BlockNumberFor;
+	use sp_runtime::traits::One;
+
+	/// Width in milliseconds of the buckets return windows were indexed by in version 4.
+	pub const EXPIRY_GRANULARITY: u64 = 60_000;
+
+	/// Products whose return window closes in a given expiry bucket, until sales were finalized
+	/// on access in version 5.
+	#[storage_alias]
+	pub type PartialProductTime<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Twox64Concat,
+		MomentOf<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::Hash,
+		(),
+	>;
+
+	#[storage_alias]
+	pub type NextExpiryBucket<T: Config> = StorageValue<Pallet<T>, MomentOf<T>>;
+
+	/// Expiry bucket of a return window closing at `deadline`.
+	pub fn expiry_bucket<T: Config>(deadline: MomentOf<T>) -> MomentOf<T> {
+		let granularity: MomentOf<T> = EXPIRY_GRANULARITY.saturated_into();
+		let bucket = deadline / granularity;
+		if (bucket * granularity) < deadline {
+			bucket + One::one()
+		} else {
+			bucket
+		}
+	}
+
+	/// Moves return windows and warranties from block numbers to milliseconds of wall-clock time.
+	///
+	/// Blocks are converted at the expected block time, relative to the upgrade block and the
+	/// timestamp of its parent.
+	pub struct MigrateToV4<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV4<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain = Pallet::<T>::on_chain_storage_version();
+			if onchain != 3 {
+				log::info!(
+					target: "runtime::template",
+					"skipping v4 migration, on-chain storage version is {:?}",
+					onchain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let n = frame_system::Pallet::<T>::block_number();
+			let now = pallet_timestamp::Pallet::<T>::get();
+			let block_time = block_time::<T>();
+			let duration = |blocks: BlockNumberFor<T>| -> MomentOf<T> {
+				blocks.saturated_into::<u64>().saturating_mul(block_time).saturated_into()
+			};
+			let moment = |block: BlockNumberFor<T>| -> MomentOf<T> {
+				if block >= n {
+					now.saturating_add(duration(block - n))
+				} else {
+					now.saturating_sub(duration(n - block))
+				}
+			};
+
+			// Both indexes keep their prefix, so read every old entry before writing a new one.
+			let pending: Vec<_> = v3::PartialSellProduct::<T>::drain().collect();
+			let _ = v3::PartialProductTime::<T>::clear(u32::MAX, None);
+			let mut next: Option<MomentOf<T>> = None;
+			for (hash, expiry) in pending.iter() {
+				let deadline = moment(*expiry);
+				let bucket = expiry_bucket::<T>(deadline);
+				PartialSellProduct::<T>::insert(hash, deadline);
+				PartialProductTime::<T>::insert(bucket, hash, ());
+				next = Some(next.map_or(bucket, |next| next.min(bucket)));
+			}
+			if let Some(next) = next {
+				NextExpiryBucket::<T>::put(next);
+			}
+
+			let mut categories = 0u64;
+			WarrantyPeriod::<T>::translate::<BlockNumberFor<T>, _>(|_, period| {
+				categories += 1;
+				Some(duration(period))
+			});
+			let mut warranties = 0u64;
+			Warranties::<T>::translate::<Warranty<T::AccountId, BlockNumberFor<T>>, _>(|_, warranty| {
+				warranties += 1;
+				Some(Warranty {
+					owner: warranty.owner,
+					expires: moment(warranty.expires),
+					status: warranty.status,
+					claim_reason: warranty.claim_reason.map(BoundedVec::truncate_from),
+				})
+			});
+			let _ = ReturnPolicy::<T>::translate::<BlockNumberFor<T>, _>(|period| period.map(duration));
+
+			StorageVersion::new(4).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::template",
+				"moved {} return windows, {} warranty periods and {} warranties to wall-clock time",
+				pending.len(),
+				categories,
+				warranties,
+			);
+
+			let pending = pending.len() as u64;
+			T::DbWeight::get().reads_writes(
+				4 + pending + categories + warranties,
+				3 + 3 * pending + categories + warranties,
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			use codec::Encode;
+
+			let pending = v3::PartialSellProduct::<T>::iter_keys().count() as u32;
+			let warranties = v3::Warranties::<T>::iter_keys().count() as u32;
+			Ok((pending, warranties).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+			use frame_support::ensure;
+
+			let (pending, warranties): (u32, u32) =
+				Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 4, "storage version not updated");
+			ensure!(Warranties::<T>::iter_keys().count() as u32 == warranties, "warranties lost");
+			ensure!(PartialSellProduct::<T>::iter_keys().count() as u32 == pending, "pending sales lost");
+
+			let next = NextExpiryBucket::<T>::get();
+			for (hash, deadline) in PartialSellProduct::<T>::iter() {
+				let bucket = expiry_bucket::<T>(deadline);
+				ensure!(PartialProductTime::<T>::contains_key(bucket, hash), "pending sale without expiry");
+				ensure!(next.map_or(false, |next| next <= bucket), "pending sale before the expiry cursor");
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v5 {
+	use super::*;
+	use crate::PartialSellProduct;
+
+	/// Drops the expiry buckets the hook used to close return windows with. Pending sales keep
+	/// their deadline and are finalized on access from version 5 on.
+	pub struct MigrateToV5<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV5<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain = Pallet::<T>::on_chain_storage_version();
+			if onchain != 4 {
+				log::info!(
+					target: "runtime::template",
+					"skipping v5 migration, on-chain storage version is {:?}",
+					onchain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let buckets = v4::PartialProductTime::<T>::clear(u32::MAX, None).unique as u64;
+			v4::NextExpiryBucket::<T>::kill();
+
+			StorageVersion::new(5).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::template",
+				"dropped {} expiry buckets",
+				buckets,
+			);
+
+			T::DbWeight::get().reads_writes(1 + buckets, 2 + buckets)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			use codec::Encode;
+
+			Ok((PartialSellProduct::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+			use frame_support::ensure;
+
+			let pending: u32 = Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 5, "storage version not updated");
+			ensure!(PartialSellProduct::<T>::iter_keys().count() as u32 == pending, "pending sales lost");
+			ensure!(v4::PartialProductTime::<T>::iter_keys().next().is_none(), "expiry buckets left behind");
+			ensure!(v4::NextExpiryBucket::<T>::get().is_none(), "expiry cursor left behind");
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v6 {
+	use super::*;
+	use crate::{
+		BalanceOf, CatalogueEndpoint, Dispute, DisputeDeadlines, Disputes, Manufacturer, ManufacturerMetadata,
+		MomentOf, Warranties, Warranty,
+	};
+	use frame_support::pallet_prelude::BoundedVec;
+	use frame_system::pallet_prelude::BlockNumberFor;
+	use sp_runtime::traits::One;
+
+	// Bound `value`, dropping what doesn't fit with a warning.
+	fn bounded<V, S: Get<u32>>(what: &str, value: Vec<V>) -> BoundedVec<V, S> {
+		if value.len() > S::get() as usize {
+			log::warn!(
+				target: "runtime::template",
+				"truncating {} of {} entries to {}",
+				what,
+				value.len(),
+				S::get(),
+			);
+		}
+		BoundedVec::truncate_from(value)
+	}
+
+	/// Moves every `Vec` in storage to a `BoundedVec` within the bounds of `Config`. Values over
+	/// their bound are truncated, and catalogue endpoints over theirs are dropped, so the bounds
+	/// must fit the chain's state before the upgrade. Disputes over `MaxDisputesPerBlock` in a
+	/// block are resolved in the next blocks with room instead, so none is left without a deadline.
+	pub struct MigrateToV6<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV6<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain = Pallet::<T>::on_chain_storage_version();
+			if onchain != 5 {
+				log::info!(
+					target: "runtime::template",
+					"skipping v6 migration, on-chain storage version is {:?}",
+					onchain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let _ = Manufacturer::<T>::translate::<Vec<T::AccountId>, _>(|manufacturers| {
+				manufacturers.map(|manufacturers| bounded("manufacturer list", manufacturers))
+			});
+			let mut entries = 1u64;
+			let mut spilled = Vec::new();
+			DisputeDeadlines::<T>::translate::<Vec<T::Hash>, _>(|block, mut hashes| {
+				entries += 1;
+				let max = T::MaxDisputesPerBlock::get() as usize;
+				if hashes.len() > max {
+					spilled.extend(hashes.drain(max..).map(|hash| (block, hash)));
+				}
+				Some(BoundedVec::truncate_from(hashes))
+			});
+			Disputes::<T>::translate::<Dispute<T::AccountId, BalanceOf<T>, BlockNumberFor<T>>, _>(|_, dispute| {
+				entries += 1;
+				Some(Dispute {
+					opener: dispute.opener,
+					customer: dispute.customer,
+					manufacturer: dispute.manufacturer,
+					evidence: bounded("dispute evidence", dispute.evidence),
+					deposit: dispute.deposit,
+					deadline: dispute.deadline,
+				})
+			});
+			for (block, hash) in spilled {
+				entries += 1;
+				if !Disputes::<T>::contains_key(&hash) {
+					continue
+				}
+				let mut deadline = block + One::one();
+				while DisputeDeadlines::<T>::try_append(deadline, hash).is_err() {
+					entries += 1;
+					deadline += One::one();
+				}
+				Disputes::<T>::mutate(&hash, |dispute| {
+					if let Some(dispute) = dispute {
+						dispute.deadline = deadline;
+					}
+				});
+				entries += 2;
+				log::warn!(
+					target: "runtime::template",
+					"moving the arbitration deadline of {:?} from block {:?} to {:?}",
+					hash,
+					block,
+					deadline,
+				);
+			}
+			Warranties::<T>::translate::<Warranty<T::AccountId, MomentOf<T>>, _>(|_, warranty| {
+				entries += 1;
+				Some(Warranty {
+					owner: warranty.owner,
+					expires: warranty.expires,
+					status: warranty.status,
+					claim_reason: warranty.claim_reason.map(|reason| bounded("claim reason", reason)),
+				})
+			});
+			ManufacturerMetadata::<T>::translate::<Vec<u8>, _>(|_, metadata| {
+				entries += 1;
+				Some(bounded("manufacturer metadata", metadata))
+			});
+			// A truncated URL is of no use to the offchain worker, the manufacturer sets it again.
+			CatalogueEndpoint::<T>::translate::<Vec<u8>, _>(|who, endpoint| {
+				entries += 1;
+				let bounded = BoundedVec::try_from(endpoint).ok();
+				if bounded.is_none() {
+					log::warn!(target: "runtime::template", "dropping catalogue endpoint of {:?}", who);
+				}
+				bounded
+			});
+
+			StorageVersion::new(6).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::template",
+				"bounded {} storage entries",
+				entries,
+			);
+
+			T::DbWeight::get().reads_writes(1 + entries, 1 + entries)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			use codec::Encode;
+
+			let disputes = Disputes::<T>::iter_keys().count() as u32;
+			let warranties = Warranties::<T>::iter_keys().count() as u32;
+			Ok((disputes, warranties).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+			use frame_support::ensure;
+
+			let (disputes, warranties): (u32, u32) =
+				Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 6, "storage version not updated");
+			ensure!(Disputes::<T>::iter().count() as u32 == disputes, "disputes lost");
+			ensure!(Warranties::<T>::iter().count() as u32 == warranties, "warranties lost");
+			for (_, hashes) in DisputeDeadlines::<T>::iter() {
+				ensure!(
+					hashes.iter().all(|hash| Disputes::<T>::contains_key(hash)),
+					"dispute deadline of an unknown dispute"
+				);
+			}
+			for (hash, dispute) in Disputes::<T>::iter() {
+				ensure!(
+					DisputeDeadlines::<T>::get(dispute.deadline).contains(&hash),
+					"dispute without a deadline"
+				);
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v7 {
+	use super::*;
+	use crate::{ProductsHash, RegisteredAt, RegistrationBlock};
+
+	/// Indexes the registration block of every product by its hash, so a product put back on sale
+	/// can be moved out of the block it was registered at.
+	///
+	/// Products still for sale are indexed at the block `ProductsHash` has for them. That is where
+	/// they were really registered when `MigrateToV2` indexed them at its upgrade block.
+	pub struct MigrateToV7<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV7<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain = Pallet::<T>::on_chain_storage_version();
+			if onchain != 6 {
+				log::info!(
+					target: "runtime::template",
+					"skipping v7 migration, on-chain storage version is {:?}",
+					onchain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let registrations: Vec<_> = RegisteredAt::<T>::iter_keys().collect();
+			let mut products = 0u64;
+			let mut moved = 0u64;
+			for (indexed, hash) in registrations {
+				products += 1;
+				let block = ProductsHash::<T>::get(&hash).unwrap_or(indexed);
+				if block != indexed {
+					RegisteredAt::<T>::remove(indexed, &hash);
+					RegisteredAt::<T>::insert(block, &hash, ());
+					moved += 1;
+				}
+				RegistrationBlock::<T>::insert(&hash, block);
+			}
+
+			StorageVersion::new(7).put::<Pallet<T>>();
+
+			log::info!(
+				target: "runtime::template",
+				"indexed the registration block of {} products, {} moved to the block they are for sale at",
+				products,
+				moved,
+			);
+
+			T::DbWeight::get().reads_writes(1 + 2 * products, 1 + products + 2 * moved)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			use codec::Encode;
+
+			Ok((RegisteredAt::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			use codec::Decode;
+			use frame_support::ensure;
+
+			let products: u32 = Decode::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+
+			ensure!(Pallet::<T>::on_chain_storage_version() >= 7, "storage version not updated");
+			ensure!(RegisteredAt::<T>::iter_keys().count() as u32 == products, "registrations lost");
+			for (block, hash) in RegisteredAt::<T>::iter_keys() {
+				ensure!(RegistrationBlock::<T>::get(&hash) == Some(block), "registration block not indexed");
+			}
+
+			Ok(())
+		}
+	}
+}
@@ -1,13 +1,20 @@
 use crate as pallet_template;
-use frame_support::traits::{ConstU16, ConstU64};
+use frame_support::{
+	traits::{ConstU16, ConstU64, ConstU8, NeverEnsureOrigin},
+	weights::{FixedFee, IdentityFee, Weight},
+	PalletId,
+};
+use frame_system::EnsureRoot;
 use sp_core::{H256, parameter_types};
 use sp_runtime::{
-	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup},
 };
+use xcm::latest::MultiLocation;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
 
 // Configure a mock runtime to test the pallet.
 frame_support::construct_runtime!(
@@ -17,6 +24,9 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system,
+		Timestamp: pallet_timestamp,
+		Balances: pallet_balances,
+		TransactionPayment: pallet_transaction_payment,
 		TemplateModule: pallet_template,
 	}
 );
@@ -39,7 +49,7 @@ impl frame_system::Config for Test {
 	type BlockHashCount = ConstU64<250>;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -48,16 +58,125 @@ impl frame_system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<3_000>;
+	type WeightInfo = ();
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+pub struct TestAuthId;
+
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+	type GenericPublic = UintAuthorityId;
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		_public: UintAuthorityId,
+		account: u64,
+		_nonce: u64,
+	) -> Option<(RuntimeCall, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (account, ())))
+	}
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = FixedFee<1, u64>;
+	type LengthToFee = IdentityFee<u64>;
+	type FeeMultiplierUpdate = ();
+}
+
 parameter_types! {
-	pub const TimeDuration: u32 = 2_16_000;
+	pub const ReturnPeriod: u64 = 3_600_000;
+	pub const DisputeDeposit: u64 = 100;
+	pub const FinalizeDeposit: u64 = 10;
+	pub const ArbitrationPeriod: u64 = 10;
+	pub const MaxCatalogueBatch: u32 = 2;
+	pub const CatalogueSyncInterval: u64 = 5;
+	pub const UnsignedPriority: u64 = 100;
+	pub const MaxVerificationsPerBlock: u32 = 2;
+	pub const TemplatePalletId: PalletId = PalletId(*b"tmpl/spn");
+	pub const MaxSponsoredPerProduct: u32 = 2;
+	pub const MaxSponsoredPerCustomer: u32 = 1;
+	pub const MaxPageSize: u32 = 4;
+	pub const MaxManufacturers: u32 = 3;
+	pub const MaxDisputesPerBlock: u32 = 1;
+	pub const MaxCidLength: u32 = 32;
+	pub const MaxEndpointLength: u32 = 32;
+	pub MaxResponseWeight: Weight = Weight::from_parts(1_000_000_000, 64 * 1024);
 }
 
 impl pallet_template::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
-	type expire_time = TimeDuration;
+	type WeightInfo = ();
+	type ReturnPeriod = ReturnPeriod;
+	type Currency = Balances;
+	type ArbiterOrigin = EnsureRoot<u64>;
+	type DisputeDeposit = DisputeDeposit;
+	type FinalizeDeposit = FinalizeDeposit;
+	type ArbitrationPeriod = ArbitrationPeriod;
+	type AuthorityId = TestAuthId;
+	type MaxCatalogueBatch = MaxCatalogueBatch;
+	type CatalogueSyncInterval = CatalogueSyncInterval;
+	type UnsignedPriority = UnsignedPriority;
+	type MaxVerificationsPerBlock = MaxVerificationsPerBlock;
+	type PalletId = TemplatePalletId;
+	type MaxSponsoredPerProduct = MaxSponsoredPerProduct;
+	type MaxSponsoredPerCustomer = MaxSponsoredPerCustomer;
+	type MaxPageSize = MaxPageSize;
+	type MaxManufacturers = MaxManufacturers;
+	type MaxDisputesPerBlock = MaxDisputesPerBlock;
+	type MaxCidLength = MaxCidLength;
+	type MaxEndpointLength = MaxEndpointLength;
+	type XcmOrigin = NeverEnsureOrigin<MultiLocation>;
+	type XcmSender = ();
+	type MaxResponseWeight = MaxResponseWeight;
+	#[cfg(feature = "runtime-benchmarks")]
+	type ReachableQuerier = frame_support::traits::GetDefault;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000), (2, 1_000), (3, 1_000)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
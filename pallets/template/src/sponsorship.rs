@@ -0,0 +1,195 @@
+//! Transaction fee sponsorship.
+//!
+//! Manufacturers prepay fees into a sponsorship account derived from the pallet's `PalletId`, so
+//! shoppers can call `check_authenticity` (or `check_authenticity_with_preimage`) and
+//! `refund_products` on their products without paying fees. [`ChargeSponsoredTransactionPayment`]
+//! replaces `pallet_transaction_payment::ChargeTransactionPayment` in the runtime's `SignedExtra`
+//! and withdraws the fee from the product manufacturer's sponsorship account while it can pay and
+//! the limits allow it, falling back to charging the signer otherwise.
+
+use crate::{
+	BalanceOf, Call, Config, Event, Pallet, ProductManufacturer, ProductOwner, SponsoredPerCustomer,
+	SponsoredPerProduct,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::{DispatchInfo, PostDispatchInfo},
+	traits::{Get, IsSubType},
+};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, Hash, PostDispatchInfoOf, SignedExtension, Zero},
+	transaction_validity::{TransactionValidity, TransactionValidityError, ValidTransaction},
+	DispatchResult,
+};
+
+type FeeBalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::Balance;
+type LiquidityInfoOf<T> =
+	<<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::LiquidityInfo;
+
+/// Charges the transaction fee to the product's manufacturer when the call is sponsored, and to
+/// the signer otherwise.
+///
+/// Encodes exactly like `ChargeTransactionPayment`, so wallets sign it the same way.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeSponsoredTransactionPayment<T: Config + pallet_transaction_payment::Config>(
+	ChargeTransactionPayment<T>,
+);
+
+impl<T: Config + pallet_transaction_payment::Config> ChargeSponsoredTransactionPayment<T> {
+	/// Utility constructor. Used only in client/factory code.
+	pub fn from(fee: FeeBalanceOf<T>) -> Self {
+		Self(ChargeTransactionPayment::<T>::from(fee))
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> sp_std::fmt::Debug
+	for ChargeSponsoredTransactionPayment<T>
+{
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ChargeSponsoredTransactionPayment<{:?}>", self.0)
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T> ChargeSponsoredTransactionPayment<T>
+where
+	T: Config + pallet_transaction_payment::Config,
+	<T as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo> + IsSubType<Call<T>>,
+{
+	// Manufacturer, product and fee if the manufacturer pays for this call.
+	fn sponsor(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+		info: &DispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		len: usize,
+	) -> Option<(T::AccountId, T::Hash, FeeBalanceOf<T>)> {
+		let hash = match call.is_sub_type() {
+			Some(Call::check_authenticity { hash }) => *hash,
+			Some(Call::check_authenticity_with_preimage { preimage }) => T::Hashing::hash(preimage),
+			// Only the customer who bought the product can return it, nobody else is sponsored.
+			Some(Call::refund_products { hash })
+				if ProductOwner::<T>::get(hash).as_ref() == Some(who) =>
+				*hash,
+			_ => return None,
+		};
+		let manufacturer = ProductManufacturer::<T>::get(&hash)?;
+
+		if SponsoredPerProduct::<T>::get(&hash) >= T::MaxSponsoredPerProduct::get() ||
+			SponsoredPerCustomer::<T>::get(&manufacturer, who) >= T::MaxSponsoredPerCustomer::get()
+		{
+			return None
+		}
+
+		// The manufacturer only covers the fee, never a tip.
+		let fee =
+			pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, Zero::zero());
+
+		Some((manufacturer, hash, fee))
+	}
+
+	// Withdraw the fee of a sponsored call from the manufacturer's sponsorship account, `None` if
+	// the call isn't sponsored or the account can't pay.
+	fn withdraw_sponsored_fee(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::RuntimeCall,
+		info: &DispatchInfoOf<<T as frame_system::Config>::RuntimeCall>,
+		len: usize,
+	) -> Option<(T::AccountId, T::Hash, FeeBalanceOf<T>, LiquidityInfoOf<T>)> {
+		let (manufacturer, hash, fee) = Self::sponsor(who, call, info, len)?;
+		let imbalance = <T as pallet_transaction_payment::Config>::OnChargeTransaction::withdraw_fee(
+			&Pallet::<T>::sponsor_account(&manufacturer),
+			call,
+			info,
+			fee,
+			Zero::zero(),
+		)
+		.ok()?;
+
+		Some((manufacturer, hash, fee, imbalance))
+	}
+}
+
+impl<T> SignedExtension for ChargeSponsoredTransactionPayment<T>
+where
+	T: Config + pallet_transaction_payment::Config + Send + Sync,
+	<T as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo> + IsSubType<Call<T>>,
+	FeeBalanceOf<T>: Send + Sync + From<u64>,
+	BalanceOf<T>: From<FeeBalanceOf<T>>,
+{
+	// Same identifier as the extension it replaces, the encoding is identical.
+	const IDENTIFIER: &'static str = "ChargeTransactionPayment";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	// The payer is the sponsorship account when the manufacturer paid.
+	type Pre = <ChargeTransactionPayment<T> as SignedExtension>::Pre;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		if let Some((_, _, fee, _)) = Self::withdraw_sponsored_fee(who, call, info, len) {
+			// Prioritized like any transaction paying the same fee without a tip.
+			return Ok(ValidTransaction {
+				priority: ChargeTransactionPayment::<T>::get_priority(info, len, Zero::zero(), fee),
+				..Default::default()
+			})
+		}
+		self.0.validate(who, call, info, len)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		if let Some((manufacturer, hash, fee, imbalance)) =
+			Self::withdraw_sponsored_fee(who, call, info, len)
+		{
+			SponsoredPerProduct::<T>::mutate(&hash, |count| *count = count.saturating_add(1));
+			SponsoredPerCustomer::<T>::mutate(&manufacturer, who, |count| {
+				*count = count.saturating_add(1)
+			});
+
+			let payer = Pallet::<T>::sponsor_account(&manufacturer);
+			Pallet::<T>::deposit_event(Event::<T>::FeeSponsored {
+				hash,
+				manufacturer,
+				who: who.clone(),
+				fee: fee.into(),
+			});
+			// `post_dispatch` refunds the unused part of the fee to the sponsorship account.
+			return Ok((Zero::zero(), payer, imbalance))
+		}
+
+		self.0.pre_dispatch(who, call, info, len)
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result)
+	}
+}
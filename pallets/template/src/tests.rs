@@ -1,6 +1,27 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
-use sp_runtime::traits::{BlakeTwo256, Hash};
+use crate::{
+	migrations::{
+		v0, v1::MigrateToV1, v2::MigrateToV2, v3::{self, MigrateToV3}, v4::{self, MigrateToV4},
+		v5::MigrateToV5, v6::MigrateToV6, v7::MigrateToV7,
+	},
+	mock::*, ChargeSponsoredTransactionPayment, Error, ProductCounts, ProductPage, ProductStatus,
+	RegisteredAt, RegistrationBlock, Ruling, WarrantyStatus,
+};
+use codec::Decode;
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::DispatchInfo,
+	traits::{
+		Currency, GenesisBuild, GetStorageVersion, Hooks, OnRuntimeUpgrade,
+		ReservableCurrency, StorageVersion,
+	},
+	unsigned::ValidateUnsigned,
+};
+use sp_core::offchain::{testing, OffchainDbExt, OffchainWorkerExt, TransactionPoolExt};
+use sp_runtime::{
+	testing::UintAuthorityId,
+	traits::{BlakeTwo256, Hash, SignedExtension},
+	transaction_validity::{InvalidTransaction, TransactionSource},
+};
 
 pub type HashType = <Test as frame_system::Config>::Hash;
 pub type Hashing = <Test as frame_system::Config>::Hashing;
@@ -42,6 +63,19 @@ fn add_duplicate_manufacture_fail() {
 	})
 }
 
+#[test]
+fn add_manufacturer_over_bound_fail() {
+	new_test_ext().execute_with(|| {
+		for who in 1..=MaxManufacturers::get() as u64 {
+			assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), who));
+		}
+		assert_noop!(
+			TemplateModule::add_manufacturer(RuntimeOrigin::root(), 0),
+			Error::<Test>::TooManyManufacturers
+		);
+	})
+}
+
 #[test]
 fn add_product_successfully() {
 	new_test_ext().execute_with(|| {
@@ -113,7 +147,7 @@ fn check_authenticity_successfully() {
 		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
 
 		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(1), hash));
-
+		System::assert_last_event(crate::Event::AuthenticProduct { hash, who: 1 }.into());
 	})
 }
 
@@ -204,3 +238,1602 @@ fn refund_products_from_incorrect_origin_fail() {
 		);
 	})
 }
+
+#[test]
+fn refund_products_by_non_owner_fail() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+
+		assert_noop!(TemplateModule::refund_products(RuntimeOrigin::signed(3), hash),
+			Error::<Test>::NotProductOwner
+		);
+	})
+}
+
+fn returned_product(hash: HashType) {
+	const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+	assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+	assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+	assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+	assert_ok!(TemplateModule::refund_products(RuntimeOrigin::signed(2), hash));
+}
+
+#[test]
+fn open_dispute_successfully() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()));
+
+		assert_eq!(Balances::reserved_balance(1), 100);
+		assert!(TemplateModule::disputes(hash).is_some());
+	})
+}
+
+#[test]
+fn open_dispute_by_stranger_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_noop!(TemplateModule::open_dispute(RuntimeOrigin::signed(3), hash, b"cid".to_vec()),
+			Error::<Test>::NotDisputeParty
+		);
+	})
+}
+
+#[test]
+fn open_dispute_on_unreturned_product_fail() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+
+		assert_noop!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()),
+			Error::<Test>::NotReturnedProduct
+		);
+	})
+}
+
+#[test]
+fn duplicate_dispute_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()));
+		assert_noop!(TemplateModule::open_dispute(RuntimeOrigin::signed(2), hash, b"cid".to_vec()),
+			Error::<Test>::DisputeAlreadyOpen
+		);
+	})
+}
+
+#[test]
+fn open_dispute_with_long_evidence_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		let evidence = vec![0; MaxCidLength::get() as usize + 1];
+		assert_noop!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, evidence),
+			Error::<Test>::CidTooLong
+		);
+	})
+}
+
+#[test]
+fn too_many_disputes_in_a_block_fail() {
+	new_test_ext().execute_with(|| {
+		let first = HashType::from(Hashing::hash_of(&42));
+		let second = HashType::from(Hashing::hash_of(&43));
+		returned_product(first);
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), second));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), second));
+		assert_ok!(TemplateModule::refund_products(RuntimeOrigin::signed(2), second));
+
+		// Both arbitration periods would end in the same block.
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), first, b"cid".to_vec()));
+		assert_noop!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), second, b"cid".to_vec()),
+			Error::<Test>::TooManyDisputes
+		);
+
+		System::set_block_number(2);
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), second, b"cid".to_vec()));
+	})
+}
+
+#[test]
+fn resolve_dispute_for_manufacturer() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(2), hash, b"cid".to_vec()));
+		assert_ok!(TemplateModule::resolve_dispute(RuntimeOrigin::root(), hash, Ruling::Manufacturer));
+
+		// The customer's deposit goes to the manufacturer and the sale is final.
+		assert_eq!(Balances::free_balance(1), 1_100);
+		assert_eq!(Balances::free_balance(2), 900);
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Sold));
+	})
+}
+
+#[test]
+fn resolve_dispute_from_invalid_origin_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(2), hash, b"cid".to_vec()));
+		assert_noop!(TemplateModule::resolve_dispute(RuntimeOrigin::signed(1), hash, Ruling::Manufacturer),
+			sp_runtime::DispatchError::BadOrigin
+		);
+	})
+}
+
+#[test]
+fn lapsed_dispute_resolves_for_customer() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()));
+
+		TemplateModule::on_initialize(1 + ArbitrationPeriod::get());
+
+		// The manufacturer's deposit goes to the customer and the product is back in stock.
+		assert!(TemplateModule::disputes(hash).is_none());
+		assert_eq!(Balances::free_balance(1), 900);
+		assert_eq!(Balances::free_balance(2), 1_100);
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Registered));
+	})
+}
+
+#[test]
+fn resolved_dispute_deadline_does_not_settle_a_later_dispute() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()));
+		assert_ok!(TemplateModule::resolve_dispute(RuntimeOrigin::root(), hash, Ruling::Customer));
+		assert!(TemplateModule::disputedeadlines(1 + ArbitrationPeriod::get()).is_empty());
+
+		// The product is sold and returned again, and the new dispute gets a later deadline.
+		System::set_block_number(2);
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+		assert_ok!(TemplateModule::refund_products(RuntimeOrigin::signed(2), hash));
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()));
+
+		TemplateModule::on_initialize(1 + ArbitrationPeriod::get());
+		assert!(TemplateModule::disputes(hash).is_some());
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Returned));
+
+		TemplateModule::on_initialize(2 + ArbitrationPeriod::get());
+		assert!(TemplateModule::disputes(hash).is_none());
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Registered));
+	})
+}
+
+#[test]
+fn stale_dispute_deadline_is_ignored() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()));
+
+		// A deadline left behind by a dispute resolved before deadlines were cleaned up.
+		let stale = ArbitrationPeriod::get();
+		crate::DisputeDeadlines::<Test>::insert(stale, frame_support::BoundedVec::truncate_from(vec![hash]));
+
+		TemplateModule::on_initialize(stale);
+		assert!(TemplateModule::disputes(hash).is_some());
+	})
+}
+
+fn sold_product(hash: HashType) {
+	const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+	assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+	assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+	assert_ok!(TemplateModule::set_product_category(RuntimeOrigin::signed(1), hash, 7));
+	assert_ok!(TemplateModule::set_warranty_period(RuntimeOrigin::root(), 7, 100));
+	assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+
+	// Return window is over.
+	close_return_window();
+}
+
+// Move the clock to the end of the return window of sales made now and have a stranger
+// finalize every pending sale in the next block, returning that block.
+fn close_return_window() -> u64 {
+	Timestamp::set_timestamp(Timestamp::now() + ReturnPeriod::get());
+	let n = System::block_number() + 1;
+	System::set_block_number(n);
+	let pending = crate::PartialSellProduct::<Test>::iter_keys().collect();
+	let _ = TemplateModule::finalize_expired(RuntimeOrigin::signed(4), pending);
+	n
+}
+
+#[test]
+fn sale_is_final_after_return_window() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+
+		System::assert_has_event(crate::Event::SaleFinalized { hash }.into());
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Sold));
+		assert_noop!(TemplateModule::refund_products(RuntimeOrigin::signed(2), hash),
+			Error::<Test>::UnsoldProduct
+		);
+	})
+}
+
+#[test]
+fn register_warranty_successfully() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+
+		assert_ok!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash));
+
+		let warranty = TemplateModule::warranties(hash).unwrap();
+		assert_eq!(warranty.owner, 2);
+		assert_eq!(warranty.expires, Timestamp::now() + 100);
+		assert_eq!(warranty.status, WarrantyStatus::Active);
+	})
+}
+
+#[test]
+fn register_warranty_before_sale_is_final_fail() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+
+		assert_noop!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash),
+			Error::<Test>::UnsoldProduct
+		);
+	})
+}
+
+#[test]
+fn register_warranty_by_non_owner_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+
+		assert_noop!(TemplateModule::register_warranty(RuntimeOrigin::signed(3), hash),
+			Error::<Test>::NotProductOwner
+		);
+	})
+}
+
+#[test]
+fn register_warranty_without_category_period_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+		assert_ok!(TemplateModule::set_product_category(RuntimeOrigin::signed(1), hash, 8));
+
+		assert_noop!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash),
+			Error::<Test>::NoWarrantyForCategory
+		);
+	})
+}
+
+#[test]
+fn warranty_claim_accepted() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+		assert_ok!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash));
+
+		assert_ok!(TemplateModule::file_warranty_claim(RuntimeOrigin::signed(2), hash, b"cid".to_vec()));
+		assert_noop!(TemplateModule::accept_warranty_claim(RuntimeOrigin::signed(2), hash),
+			Error::<Test>::UnAuthorisedPerson
+		);
+		assert_ok!(TemplateModule::accept_warranty_claim(RuntimeOrigin::signed(1), hash));
+
+		assert_eq!(TemplateModule::warranties(hash).unwrap().status, WarrantyStatus::Accepted);
+		assert_noop!(TemplateModule::file_warranty_claim(RuntimeOrigin::signed(2), hash, b"cid".to_vec()),
+			Error::<Test>::InvalidWarrantyStatus
+		);
+	})
+}
+
+#[test]
+fn warranty_claim_rejected_can_be_refiled() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+		assert_ok!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash));
+
+		assert_ok!(TemplateModule::file_warranty_claim(RuntimeOrigin::signed(2), hash, b"cid".to_vec()));
+		assert_ok!(TemplateModule::reject_warranty_claim(RuntimeOrigin::signed(1), hash));
+		assert_eq!(TemplateModule::warranties(hash).unwrap().status, WarrantyStatus::Rejected);
+
+		assert_ok!(TemplateModule::file_warranty_claim(RuntimeOrigin::signed(2), hash, b"cid2".to_vec()));
+	})
+}
+
+#[test]
+fn warranty_claim_with_long_reason_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+		assert_ok!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash));
+
+		let reason = vec![0; MaxCidLength::get() as usize + 1];
+		assert_noop!(TemplateModule::file_warranty_claim(RuntimeOrigin::signed(2), hash, reason),
+			Error::<Test>::CidTooLong
+		);
+	})
+}
+
+#[test]
+fn expired_warranty_claim_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+		assert_ok!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash));
+
+		Timestamp::set_timestamp(Timestamp::now() + 100);
+		assert_ok!(TemplateModule::file_warranty_claim(RuntimeOrigin::signed(2), hash, b"cid".to_vec()));
+		assert_ok!(TemplateModule::reject_warranty_claim(RuntimeOrigin::signed(1), hash));
+
+		Timestamp::set_timestamp(Timestamp::now() + 1);
+		assert_noop!(TemplateModule::file_warranty_claim(RuntimeOrigin::signed(2), hash, b"cid".to_vec()),
+			Error::<Test>::WarrantyExpired
+		);
+	})
+}
+
+#[test]
+fn offchain_worker_submits_new_catalogue_products() {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	let registered = HashType::from(Hashing::hash_of(&42));
+	let fresh = HashType::from(Hashing::hash_of(&43));
+	offchain_state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: "http://localhost:8000/catalogue".into(),
+		response: Some(format!("{:?}\nnot-a-hash\n{:?}\n", registered, fresh).into_bytes()),
+		sent: true,
+		..Default::default()
+	});
+
+	t.execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+		UintAuthorityId::set_all_keys(vec![TEST_ACCOUNT]);
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), registered));
+		assert_ok!(TemplateModule::set_catalogue_endpoint(
+			RuntimeOrigin::signed(1),
+			b"http://localhost:8000/catalogue".to_vec()
+		));
+
+		TemplateModule::offchain_worker(1);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, Some((1, ())));
+		assert_eq!(
+			tx.call,
+			RuntimeCall::TemplateModule(crate::Call::add_products { hashes: vec![fresh] })
+		);
+
+		// Nothing is fetched again until the sync interval is over.
+		TemplateModule::offchain_worker(2);
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn offchain_worker_skips_broken_catalogues() {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	let fresh = HashType::from(Hashing::hash_of(&43));
+	offchain_state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: "http://localhost:8000/broken".into(),
+		response: Some(vec![0xff, 0xfe]),
+		sent: true,
+		..Default::default()
+	});
+	offchain_state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: "http://localhost:8000/catalogue".into(),
+		response: Some(format!("{:?}\n", fresh).into_bytes()),
+		sent: true,
+		..Default::default()
+	});
+
+	t.execute_with(|| {
+		UintAuthorityId::set_all_keys(vec![1u64, 2, 3]);
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 1));
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 2));
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 3));
+		// The first catalogue isn't utf-8 and the second one's URL isn't either.
+		assert_ok!(TemplateModule::set_catalogue_endpoint(
+			RuntimeOrigin::signed(1),
+			b"http://localhost:8000/broken".to_vec()
+		));
+		assert_ok!(TemplateModule::set_catalogue_endpoint(RuntimeOrigin::signed(2), vec![0xff]));
+		assert_ok!(TemplateModule::set_catalogue_endpoint(
+			RuntimeOrigin::signed(3),
+			b"http://localhost:8000/catalogue".to_vec()
+		));
+
+		TemplateModule::offchain_worker(1);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, Some((3, ())));
+		assert_eq!(
+			tx.call,
+			RuntimeCall::TemplateModule(crate::Call::add_products { hashes: vec![fresh] })
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_skips_accounts_that_are_not_manufacturers() {
+	let (offchain, _) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain.clone()));
+	t.register_extension(OffchainDbExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	t.execute_with(|| {
+		UintAuthorityId::set_all_keys(vec![2u64]);
+
+		TemplateModule::offchain_worker(1);
+
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn add_products_successfully() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let first = HashType::from(Hashing::hash_of(&42));
+		let second = HashType::from(Hashing::hash_of(&43));
+
+		assert_ok!(TemplateModule::add_products(RuntimeOrigin::signed(1), vec![first, second]));
+
+		assert_eq!(TemplateModule::productmanufacturer(first), Some(1));
+		assert_eq!(TemplateModule::productmanufacturer(second), Some(1));
+	})
+}
+
+#[test]
+fn add_too_many_products_fail() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hashes = (0..3u32).map(|i| HashType::from(Hashing::hash_of(&i))).collect();
+
+		assert_noop!(TemplateModule::add_products(RuntimeOrigin::signed(1), hashes),
+			Error::<Test>::TooManyProducts
+		);
+	})
+}
+
+#[test]
+fn set_catalogue_endpoint_by_unauthorised_person_fail() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::set_catalogue_endpoint(RuntimeOrigin::signed(2), b"http://localhost".to_vec()),
+			Error::<Test>::UnAuthorisedPerson
+		);
+	})
+}
+
+#[test]
+fn set_long_catalogue_endpoint_fail() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		assert_noop!(
+			TemplateModule::set_catalogue_endpoint(
+				RuntimeOrigin::signed(1),
+				b"http://localhost:8000/catalogue.json".to_vec()
+			),
+			Error::<Test>::EndpointTooLong
+		);
+	})
+}
+
+#[test]
+fn record_verification_successfully() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+
+		assert_ok!(TemplateModule::record_verification(RuntimeOrigin::none(), hash));
+
+		// Verifying doesn't sell the product.
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Registered));
+		assert_eq!(TemplateModule::verificationcount(hash), 1);
+		assert_noop!(TemplateModule::record_verification(RuntimeOrigin::none(), hash),
+			Error::<Test>::AlreadyVerified
+		);
+
+		System::set_block_number(2);
+		assert_ok!(TemplateModule::record_verification(RuntimeOrigin::none(), hash));
+		assert_eq!(TemplateModule::verificationcount(hash), 2);
+	})
+}
+
+#[test]
+fn record_verification_from_signed_origin_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+
+		assert_noop!(TemplateModule::record_verification(RuntimeOrigin::signed(1), hash),
+			sp_runtime::DispatchError::BadOrigin
+		);
+	})
+}
+
+#[test]
+fn validate_unsigned_verification() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		let hash = HashType::from(Hashing::hash_of(&42));
+		let call = crate::Call::record_verification { hash };
+
+		// Unknown products can't be used to spam the pool.
+		assert_eq!(
+			TemplateModule::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into()
+		);
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+		let valid = TemplateModule::validate_unsigned(TransactionSource::External, &call).unwrap();
+		assert_eq!(valid.priority, UnsignedPriority::get());
+
+		// Only one verification per product per block.
+		assert_ok!(TemplateModule::record_verification(RuntimeOrigin::none(), hash));
+		assert_eq!(
+			TemplateModule::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into()
+		);
+	})
+}
+
+#[test]
+fn verifications_are_capped_per_block() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hashes: Vec<HashType> = (1..=3).map(|i| HashType::from(Hashing::hash_of(&i))).collect();
+		for hash in hashes.iter() {
+			assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), *hash));
+		}
+
+		// The mock takes two verifications per block.
+		assert_ok!(TemplateModule::record_verification(RuntimeOrigin::none(), hashes[0]));
+		assert_ok!(TemplateModule::record_verification(RuntimeOrigin::none(), hashes[1]));
+		let call = crate::Call::record_verification { hash: hashes[2] };
+		assert_eq!(
+			TemplateModule::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::ExhaustsResources.into()
+		);
+		assert_noop!(TemplateModule::record_verification(RuntimeOrigin::none(), hashes[2]),
+			Error::<Test>::TooManyVerifications
+		);
+
+		// The count starts over in the next block.
+		System::set_block_number(2);
+		assert_ok!(TemplateModule::validate_unsigned(TransactionSource::External, &call));
+		assert_ok!(TemplateModule::record_verification(RuntimeOrigin::none(), hashes[2]));
+		assert_eq!(TemplateModule::verificationsinblock(), (2, 1));
+	})
+}
+
+#[test]
+fn fund_and_withdraw_sponsorship() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		assert_ok!(TemplateModule::fund_sponsorship(RuntimeOrigin::signed(1), 500));
+		assert_eq!(TemplateModule::sponsorship(&1), 500);
+		assert_eq!(Balances::free_balance(TemplateModule::sponsor_account(&1)), 500);
+		assert_eq!(Balances::free_balance(1), 500);
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		assert_noop!(TemplateModule::withdraw_sponsorship(RuntimeOrigin::signed(1), 501),
+			Error::<Test>::InsufficientSponsorship
+		);
+		assert_ok!(TemplateModule::withdraw_sponsorship(RuntimeOrigin::signed(1), 200));
+		assert_eq!(TemplateModule::sponsorship(&1), 300);
+		assert_eq!(Balances::free_balance(1), 700);
+	})
+}
+
+#[test]
+fn fund_sponsorship_by_unauthorised_person_fail() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(TemplateModule::fund_sponsorship(RuntimeOrigin::signed(2), 500),
+			Error::<Test>::UnAuthorisedPerson
+		);
+	})
+}
+
+#[test]
+fn manufacturer_pays_for_sponsored_calls() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+		assert_ok!(TemplateModule::fund_sponsorship(RuntimeOrigin::signed(1), 500));
+		let sponsor = TemplateModule::sponsor_account(&1);
+
+		let info = DispatchInfo::default();
+		let len = 10;
+		let fee = TransactionPayment::compute_fee(len as u32, &info, 0);
+		let call = RuntimeCall::TemplateModule(crate::Call::check_authenticity { hash });
+
+		// Sponsored calls get the priority of any call paying the same fee.
+		let valid = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.validate(&2, &call, &info, len)
+			.unwrap();
+		assert_eq!(
+			valid.priority,
+			pallet_transaction_payment::ChargeTransactionPayment::<Test>::get_priority(&info, len, 0, fee)
+		);
+
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&2, &call, &info, len)
+			.unwrap();
+		assert_eq!(pre.1, sponsor);
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&Default::default(),
+			len,
+			&Ok(())
+		));
+
+		// The fee comes out of the sponsorship account, the manufacturer's deposits are untouched.
+		assert_eq!(Balances::free_balance(2), 1_000);
+		assert_eq!(TemplateModule::sponsorship(&1), 500 - fee);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(TemplateModule::sponsoredperproduct(hash), 1);
+		assert_eq!(TemplateModule::sponsoredpercustomer(1, 2), 1);
+
+		// Customer limit reached, the signer pays.
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&2, &call, &info, len)
+			.unwrap();
+		assert_eq!(pre.1, 2);
+		assert_eq!(Balances::free_balance(2), 1_000 - fee);
+		assert_eq!(TemplateModule::sponsorship(&1), 500 - fee);
+	})
+}
+
+#[test]
+fn sponsored_customer_without_funds_buys_a_product() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+		const SHOPPER: <Test as frame_system::Config>::AccountId = 5;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+		assert_ok!(TemplateModule::fund_sponsorship(RuntimeOrigin::signed(1), 500));
+		assert_eq!(Balances::total_balance(&SHOPPER), 0);
+
+		let info = DispatchInfo::default();
+		let len = 10;
+		let fee = TransactionPayment::compute_fee(len as u32, &info, 0);
+		let call = RuntimeCall::TemplateModule(crate::Call::check_authenticity { hash });
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&SHOPPER, &call, &info, len)
+			.unwrap();
+		assert_eq!(pre.1, TemplateModule::sponsor_account(&1));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(SHOPPER), hash));
+
+		// The finalization deposit is reserved apart from the sponsorship.
+		assert_eq!(TemplateModule::productowner(hash), Some(SHOPPER));
+		assert_eq!(TemplateModule::finalizedeposit(hash), Some((1, FinalizeDeposit::get())));
+		assert_eq!(Balances::reserved_balance(1), FinalizeDeposit::get());
+		assert_eq!(TemplateModule::sponsorship(&1), 500 - fee);
+		assert_eq!(Balances::total_balance(&SHOPPER), 0);
+		assert_ok!(TemplateModule::do_try_state(1));
+	})
+}
+
+#[test]
+fn signer_pays_for_unsponsored_calls() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+
+		let info = DispatchInfo::default();
+		let len = 10;
+		let fee = TransactionPayment::compute_fee(len as u32, &info, 0);
+
+		// No sponsorship balance.
+		let call = RuntimeCall::TemplateModule(crate::Call::check_authenticity { hash });
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&2, &call, &info, len)
+			.unwrap();
+		assert_eq!(pre.1, 2);
+		assert_eq!(Balances::free_balance(2), 1_000 - fee);
+
+		// Other calls are never sponsored.
+		assert_ok!(TemplateModule::fund_sponsorship(RuntimeOrigin::signed(1), 500));
+		let call = RuntimeCall::TemplateModule(crate::Call::add_product { hash });
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&1, &call, &info, len)
+			.unwrap();
+		assert_eq!(pre.1, 1);
+		assert_eq!(TemplateModule::sponsorship(&1), 500);
+
+		// Only the owner's returns are sponsored.
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+		let call = RuntimeCall::TemplateModule(crate::Call::refund_products { hash });
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&3, &call, &info, len)
+			.unwrap();
+		assert_eq!(pre.1, 3);
+		assert_eq!(TemplateModule::sponsorship(&1), 500);
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&2, &call, &info, len)
+			.unwrap();
+		assert_eq!(pre.1, TemplateModule::sponsor_account(&1));
+	})
+}
+
+#[test]
+fn sales_sharing_an_expiry_block_all_become_final() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let first = HashType::from(Hashing::hash_of(&1));
+		let second = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), first));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), second));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), first));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(3), second));
+
+		close_return_window();
+
+		assert_eq!(TemplateModule::product_status(&first), Some(ProductStatus::Sold));
+		assert_eq!(TemplateModule::product_status(&second), Some(ProductStatus::Sold));
+	})
+}
+
+#[test]
+fn add_sold_product_again_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+
+		assert_noop!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash),
+			Error::<Test>::ProductAlreadyPresent
+		);
+	})
+}
+
+#[test]
+fn migrate_to_v1_moves_lists_to_maps() {
+	new_test_ext().execute_with(|| {
+		let registered = HashType::from(Hashing::hash_of(&1));
+		let pending = HashType::from(Hashing::hash_of(&2));
+		let overwritten = HashType::from(Hashing::hash_of(&3));
+		let expired = HashType::from(Hashing::hash_of(&4));
+		let sold = HashType::from(Hashing::hash_of(&5));
+		let returned = HashType::from(Hashing::hash_of(&6));
+
+		System::set_block_number(10);
+		StorageVersion::new(0).put::<TemplateModule>();
+		v0::ProductsHash::<Test>::put(vec![registered]);
+		v0::PartialSellProduct::<Test>::put(vec![pending, overwritten, expired]);
+		v0::SellProducts::<Test>::put(vec![sold]);
+		v0::ReturnedProducts::<Test>::put(vec![returned]);
+		v0::PartialProductTime::<Test>::insert(20, pending);
+		v0::PartialProductTime::<Test>::insert(5, expired);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 1);
+		assert_eq!(TemplateModule::productshash(registered), Some(10));
+		assert_eq!(TemplateModule::selledproducts(sold), Some(10));
+		assert_eq!(TemplateModule::retunedproducts(returned), Some(10));
+
+		// The recorded window is kept, a lost one starts over and a closed one is final. Windows are
+		// still counted in blocks, at 6s per block.
+		assert_eq!(v3::PartialSellProduct::<Test>::get(pending), Some(20));
+		assert!(v3::PartialProductTime::<Test>::contains_key(20, pending));
+		let fresh = 10 + ReturnPeriod::get() / 6_000;
+		assert_eq!(v3::PartialSellProduct::<Test>::get(overwritten), Some(fresh));
+		assert!(v3::PartialProductTime::<Test>::contains_key(fresh, overwritten));
+		assert_eq!(TemplateModule::product_status(&expired), Some(ProductStatus::Sold));
+		assert!(!v3::PartialProductTime::<Test>::contains_key(5, expired));
+	})
+}
+
+#[test]
+fn legacy_products_migrate_to_latest_version() {
+	new_test_ext().execute_with(|| {
+		let registered = HashType::from(Hashing::hash_of(&1));
+		let pending = HashType::from(Hashing::hash_of(&2));
+		let sold = HashType::from(Hashing::hash_of(&3));
+		let returned = HashType::from(Hashing::hash_of(&4));
+
+		System::set_block_number(10);
+		Timestamp::set_timestamp(600_000);
+		StorageVersion::new(0).put::<TemplateModule>();
+		v0::Manufacturer::<Test>::put(vec![1]);
+		v0::ProductsHash::<Test>::put(vec![registered]);
+		v0::PartialSellProduct::<Test>::put(vec![pending]);
+		v0::SellProducts::<Test>::put(vec![sold]);
+		v0::ReturnedProducts::<Test>::put(vec![returned]);
+		v0::PartialProductTime::<Test>::insert(20, pending);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		MigrateToV2::<Test>::on_runtime_upgrade();
+		MigrateToV3::<Test>::on_runtime_upgrade();
+		MigrateToV4::<Test>::on_runtime_upgrade();
+		MigrateToV5::<Test>::on_runtime_upgrade();
+		MigrateToV6::<Test>::on_runtime_upgrade();
+		MigrateToV7::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 7);
+		for hash in [registered, pending, sold, returned] {
+			assert_eq!(TemplateModule::productmanufacturer(hash), Some(1));
+		}
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(1, 1, 1, 1));
+		assert_ok!(TemplateModule::do_try_state(10));
+
+		// Legacy products can't be registered again.
+		assert_noop!(TemplateModule::add_product(RuntimeOrigin::signed(1), sold),
+			Error::<Test>::ProductAlreadyPresent
+		);
+	})
+}
+
+#[test]
+fn migrate_to_v1_skips_migrated_storage() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		StorageVersion::new(1).put::<TemplateModule>();
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::productshash(hash), Some(1));
+	})
+}
+
+#[test]
+fn try_state_holds_through_the_lifecycle() {
+	new_test_ext().execute_with(|| {
+		let sold = HashType::from(Hashing::hash_of(&1));
+		sold_product(sold);
+		let returned = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), returned));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(3), returned));
+		assert_ok!(TemplateModule::refund_products(RuntimeOrigin::signed(3), returned));
+		let registered = HashType::from(Hashing::hash_of(&3));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), registered));
+
+		assert_ok!(TemplateModule::do_try_state(System::block_number()));
+	})
+}
+
+#[test]
+fn try_state_detects_broken_invariants() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+		assert_ok!(TemplateModule::do_try_state(1));
+
+		// The deposit outlived the sale.
+		crate::FinalizeDeposits::<Test>::insert(HashType::from(Hashing::hash_of(&7)), (2, 10));
+		assert_eq!(TemplateModule::do_try_state(1), Err("finalization deposit of a settled sale"));
+		crate::FinalizeDeposits::<Test>::remove(HashType::from(Hashing::hash_of(&7)));
+
+		crate::SellProducts::<Test>::insert(hash, 1);
+		assert_eq!(TemplateModule::do_try_state(1), Err("product is in several lifecycle sets"));
+	})
+}
+
+#[test]
+fn genesis_registers_manufacturers_and_products() {
+	let hash = HashType::from(Hashing::hash_of(&42));
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		manufacturers: vec![(2, b"Bob Manufacturing".to_vec()), (1, b"Alice Manufacturing".to_vec())],
+		products: vec![(1, hash)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	sp_io::TestExternalities::from(t).execute_with(|| {
+		assert_eq!(TemplateModule::manufacturers(), vec![1, 2]);
+		assert_eq!(TemplateModule::manufacturermetadata(2).unwrap(), b"Bob Manufacturing".to_vec());
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Registered));
+		assert_eq!(TemplateModule::productmanufacturer(hash), Some(1));
+	})
+}
+
+#[test]
+#[should_panic(expected = "genesis product of an unknown manufacturer")]
+fn genesis_product_of_unknown_manufacturer_fail() {
+	let hash = HashType::from(Hashing::hash_of(&42));
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> { manufacturers: vec![(1, Vec::new())], products: vec![(2, hash)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+}
+
+// Follow `next` until the last page, checking that no page is larger than `MaxPageSize`.
+fn all_pages<C>(mut page: impl FnMut(Option<C>) -> ProductPage<HashType, C>) -> Vec<HashType> {
+	let mut products = Vec::new();
+	let mut cursor = None;
+	loop {
+		let ProductPage { products: page, next } = page(cursor);
+		assert!(page.len() <= MaxPageSize::get() as usize);
+		products.extend(page);
+		match next {
+			Some(next) => cursor = Some(next),
+			None => return products,
+		}
+	}
+}
+
+fn sorted(mut products: Vec<HashType>) -> Vec<HashType> {
+	products.sort();
+	products
+}
+
+#[test]
+fn list_products_by_status_in_pages() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 1));
+		let products: Vec<HashType> = (0..10u32).map(|i| HashType::from(Hashing::hash_of(&i))).collect();
+		for hash in products.iter() {
+			assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), *hash));
+		}
+		for hash in products[..3].iter() {
+			assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(3), *hash));
+		}
+
+		let registered =
+			all_pages(|cursor| TemplateModule::products_by_status(ProductStatus::Registered, cursor, 100));
+		assert_eq!(sorted(registered), sorted(products[3..].to_vec()));
+		let pending =
+			all_pages(|cursor| TemplateModule::products_by_status(ProductStatus::PendingReturn, cursor, 2));
+		assert_eq!(sorted(pending), sorted(products[..3].to_vec()));
+		assert!(all_pages(|cursor| TemplateModule::products_by_status(ProductStatus::Sold, cursor, 2))
+			.is_empty());
+
+		let page = TemplateModule::products_by_status(ProductStatus::Registered, None, 2);
+		assert_eq!(page.products.len(), 2);
+		assert_eq!(page.next, page.products.last().copied());
+	})
+}
+
+#[test]
+fn list_products_by_manufacturer_in_pages() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 1));
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 2));
+		let mut bobs = Vec::new();
+		for i in 0..10u32 {
+			let hash = HashType::from(Hashing::hash_of(&i));
+			let manufacturer = if i % 2 == 0 { 1 } else { 2 };
+			assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(manufacturer), hash));
+			if manufacturer == 2 {
+				bobs.push(hash);
+			}
+		}
+
+		let listed = all_pages(|cursor| TemplateModule::products_by_manufacturer(2, cursor, 2));
+		assert_eq!(sorted(listed), sorted(bobs));
+		assert!(all_pages(|cursor| TemplateModule::products_by_manufacturer(3, cursor, 2)).is_empty());
+	})
+}
+
+#[test]
+fn list_products_registered_between_blocks() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 1));
+		let mut blocks = Vec::new();
+		for (i, block) in [1u64, 2, 3, 3, 4, 5].into_iter().enumerate() {
+			System::set_block_number(block);
+			let hash = HashType::from(Hashing::hash_of(&i));
+			assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+			blocks.push((block, hash));
+		}
+
+		let listed = all_pages(|cursor| TemplateModule::products_registered_between(2, 4, cursor, 1));
+		let block_of = |hash| blocks.iter().find(|(_, product)| *product == hash).unwrap().0;
+		assert_eq!(listed.iter().map(|hash| block_of(*hash)).collect::<Vec<_>>(), vec![2, 3, 3, 4]);
+
+		// Empty blocks count against the page, so a wide empty range is still read in pages.
+		let page = TemplateModule::products_registered_between(100, 10_000, None, 10);
+		assert!(page.products.is_empty());
+		assert!(page.next.is_some());
+		assert!(all_pages(|cursor| TemplateModule::products_registered_between(100, 1_000, cursor, 10))
+			.is_empty());
+	})
+}
+
+#[test]
+fn refunded_product_is_registered_again_at_the_ruling() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(2), hash, b"cid".to_vec()));
+
+		System::set_block_number(5);
+		assert_ok!(TemplateModule::resolve_dispute(RuntimeOrigin::root(), hash, Ruling::Customer));
+
+		assert_eq!(TemplateModule::productshash(hash), Some(5));
+		assert_eq!(TemplateModule::registrationblock(hash), Some(5));
+		assert!(TemplateModule::registeredat(1, hash).is_none());
+		let registered_between =
+			|from, to| all_pages(|cursor| TemplateModule::products_registered_between(from, to, cursor, 10));
+		assert!(registered_between(1, 4).is_empty());
+		assert_eq!(registered_between(5, 5), vec![hash]);
+		assert_eq!(all_pages(|cursor| TemplateModule::products_by_manufacturer(1, cursor, 10)), vec![hash]);
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(1, 0, 0, 0));
+		assert_ok!(TemplateModule::do_try_state(5));
+	})
+}
+
+#[test]
+fn migrate_to_v2_indexes_registration_blocks() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 1));
+		let registered = HashType::from(Hashing::hash_of(&1));
+		let sold = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), registered));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), sold));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(3), sold));
+
+		// Version 1 storage had no registration index.
+		StorageVersion::new(1).put::<TemplateModule>();
+		let _ = RegisteredAt::<Test>::clear(u32::MAX, None);
+		let _ = RegistrationBlock::<Test>::clear(u32::MAX, None);
+		System::set_block_number(7);
+
+		MigrateToV2::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 2);
+		assert!(TemplateModule::registeredat(7, registered).is_some());
+		assert!(TemplateModule::registeredat(7, sold).is_some());
+
+		// The index is only complete with the reverse index of version 7, which versions 3 to 6
+		// don't touch.
+		StorageVersion::new(6).put::<TemplateModule>();
+		MigrateToV7::<Test>::on_runtime_upgrade();
+		assert_ok!(TemplateModule::do_try_state(7));
+	})
+}
+
+fn counts(registered: u32, pending: u32, sold: u32, returned: u32) -> ProductCounts {
+	ProductCounts { registered, pending, sold, returned }
+}
+
+#[test]
+fn manufacturer_counters_follow_the_lifecycle() {
+	new_test_ext().execute_with(|| {
+		let returned = HashType::from(Hashing::hash_of(&1));
+		returned_product(returned);
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(0, 0, 0, 1));
+
+		let sold = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), sold));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(3), sold));
+		let stock = HashType::from(Hashing::hash_of(&3));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), stock));
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(1, 1, 0, 1));
+
+		// The refund stands, so the returned product is back in stock.
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(2), returned, b"cid".to_vec()));
+		assert_ok!(TemplateModule::resolve_dispute(RuntimeOrigin::root(), returned, Ruling::Customer));
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(2, 1, 0, 0));
+
+		let n = close_return_window();
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(2, 0, 1, 0));
+
+		let indexed = crate::ManufacturerProducts::<Test>::iter_key_prefix(1).collect();
+		assert_eq!(sorted(indexed), sorted(vec![returned, sold, stock]));
+		assert_eq!(TemplateModule::manufacturercounts(2), ProductCounts::default());
+		assert_ok!(TemplateModule::do_try_state(n));
+	})
+}
+
+#[test]
+fn manufacturer_counts_report_expired_sales_as_sold() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		sold_product(hash);
+		let pending = HashType::from(Hashing::hash_of(&43));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), pending));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), pending));
+		assert_eq!(TemplateModule::manufacturer_counts(&1), counts(0, 1, 1, 0));
+
+		// Nobody finalized the sale, the stored counters still have it pending.
+		Timestamp::set_timestamp(Timestamp::now() + ReturnPeriod::get());
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(0, 1, 1, 0));
+		assert_eq!(TemplateModule::manufacturer_counts(&1), counts(0, 0, 2, 0));
+		assert_eq!(TemplateModule::manufacturer_counts(&2), ProductCounts::default());
+	})
+}
+
+#[test]
+fn migrate_to_v3_builds_manufacturer_index() {
+	new_test_ext().execute_with(|| {
+		let returned = HashType::from(Hashing::hash_of(&1));
+		returned_product(returned);
+		let registered = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), registered));
+
+		// Version 2 storage had neither the index nor the counters.
+		StorageVersion::new(2).put::<TemplateModule>();
+		let _ = crate::ManufacturerProducts::<Test>::clear(u32::MAX, None);
+		let _ = crate::ManufacturerCounts::<Test>::clear(u32::MAX, None);
+
+		MigrateToV3::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 3);
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(1, 0, 0, 1));
+		assert!(TemplateModule::manufacturerproducts(1, returned).is_some());
+		assert!(TemplateModule::manufacturerproducts(1, registered).is_some());
+		assert_ok!(TemplateModule::do_try_state(1));
+	})
+}
+
+#[test]
+fn committed_product_is_sold_against_its_preimage() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let preimage = b"sku-1/lot-7/serial-42/salt-3f9a".to_vec();
+		let commitment = HashType::from(BlakeTwo256::hash(&preimage));
+		assert_ok!(TemplateModule::add_product_commitment(RuntimeOrigin::signed(1), commitment));
+		assert!(TemplateModule::committedproducts(commitment).is_some());
+
+		assert_noop!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), commitment),
+			Error::<Test>::PreimageRequired
+		);
+		assert_noop!(
+			TemplateModule::check_authenticity_with_preimage(RuntimeOrigin::signed(2), b"guess".to_vec()),
+			Error::<Test>::UnAuthenticProduct
+		);
+
+		assert_ok!(TemplateModule::check_authenticity_with_preimage(RuntimeOrigin::signed(2), preimage.clone()));
+
+		System::assert_last_event(crate::Event::AuthenticProduct { hash: commitment, who: 2 }.into());
+		assert_eq!(TemplateModule::product_status(&commitment), Some(ProductStatus::PendingReturn));
+		assert_eq!(TemplateModule::productowner(commitment), Some(2));
+		assert!(TemplateModule::committedproducts(commitment).is_none());
+		assert_noop!(TemplateModule::check_authenticity_with_preimage(RuntimeOrigin::signed(3), preimage),
+			Error::<Test>::UnAuthenticProduct
+		);
+		assert_ok!(TemplateModule::do_try_state(1));
+	})
+}
+
+#[test]
+fn check_authenticity_with_long_preimage_fail() {
+	new_test_ext().execute_with(|| {
+		let preimage = vec![0u8; crate::MAX_PREIMAGE_LENGTH as usize + 1];
+
+		assert_noop!(TemplateModule::check_authenticity_with_preimage(RuntimeOrigin::signed(2), preimage),
+			Error::<Test>::PreimageTooLong
+		);
+	})
+}
+
+#[test]
+fn add_product_commitment_by_unauthorised_person_fail() {
+	new_test_ext().execute_with(|| {
+		let commitment = HashType::from(Hashing::hash_of(&42));
+
+		assert_noop!(TemplateModule::add_product_commitment(RuntimeOrigin::signed(2), commitment),
+			Error::<Test>::UnAuthorisedPerson
+		);
+	})
+}
+
+#[test]
+fn return_policy_applies_to_future_sales() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let before = HashType::from(Hashing::hash_of(&1));
+		let after = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), before));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), after));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), before));
+
+		// Half an hour, shorter than the configured window.
+		assert_ok!(TemplateModule::set_return_period(RuntimeOrigin::root(), Some(1_800_000)));
+		System::assert_last_event(crate::Event::ReturnPeriodSet { period: Some(1_800_000) }.into());
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), after));
+
+		assert_eq!(TemplateModule::partialsellproduct(before), Some(ReturnPeriod::get()));
+		assert_eq!(TemplateModule::partialsellproduct(after), Some(1_800_000));
+
+		Timestamp::set_timestamp(1_800_000);
+		assert_eq!(TemplateModule::product_status(&after), Some(ProductStatus::Sold));
+		assert_eq!(TemplateModule::product_status(&before), Some(ProductStatus::PendingReturn));
+
+		// Clearing the policy goes back to the configured period.
+		assert_ok!(TemplateModule::set_return_period(RuntimeOrigin::root(), None));
+		assert_eq!(TemplateModule::return_period(), ReturnPeriod::get());
+	})
+}
+
+#[test]
+fn set_return_period_fail() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(TemplateModule::set_return_period(RuntimeOrigin::signed(1), Some(50)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(TemplateModule::set_return_period(RuntimeOrigin::root(), Some(0)),
+			Error::<Test>::InvalidReturnPeriod
+		);
+	})
+}
+
+#[test]
+fn return_window_follows_the_clock_not_blocks() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+		Timestamp::set_timestamp(1_000);
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+		let deadline = 1_000 + ReturnPeriod::get();
+		assert_eq!(TemplateModule::partialsellproduct(hash), Some(deadline));
+
+		// However many blocks pass, the window stays open until its deadline.
+		System::set_block_number(1_000_000);
+		Timestamp::set_timestamp(deadline - 1);
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::PendingReturn));
+		assert_noop!(TemplateModule::finalize_expired(RuntimeOrigin::signed(3), vec![hash]),
+			Error::<Test>::NothingToFinalize
+		);
+
+		// Past the deadline the sale is final, even before anyone finalizes it.
+		Timestamp::set_timestamp(deadline);
+		assert_eq!(TemplateModule::product_status(&hash), Some(ProductStatus::Sold));
+		assert_noop!(TemplateModule::refund_products(RuntimeOrigin::signed(2), hash),
+			Error::<Test>::ReturnWindowClosed
+		);
+		assert_eq!(TemplateModule::products_by_status(ProductStatus::Sold, None, 4).products, vec![hash]);
+		assert!(TemplateModule::products_by_status(ProductStatus::PendingReturn, None, 4).products.is_empty());
+		assert_ok!(TemplateModule::do_try_state(1_000_000));
+	})
+}
+
+#[test]
+fn finalize_expired_pays_the_deposit_to_the_caller() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let expired = HashType::from(Hashing::hash_of(&1));
+		let open = HashType::from(Hashing::hash_of(&2));
+		let unknown = HashType::from(Hashing::hash_of(&3));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), expired));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), open));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), expired));
+		assert_eq!(Balances::reserved_balance(1), FinalizeDeposit::get());
+		assert_eq!(TemplateModule::finalizedeposit(expired), Some((1, FinalizeDeposit::get())));
+
+		Timestamp::set_timestamp(ReturnPeriod::get());
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), open));
+
+		// Only the sale whose window is over is finalized, the rest is skipped.
+		assert_ok!(TemplateModule::finalize_expired(RuntimeOrigin::signed(3), vec![expired, open, unknown]));
+		System::assert_has_event(crate::Event::SaleFinalized { hash: expired }.into());
+		assert_eq!(TemplateModule::selledproducts(expired), Some(1));
+		assert_eq!(TemplateModule::product_status(&open), Some(ProductStatus::PendingReturn));
+		assert_eq!(TemplateModule::manufacturercounts(1), counts(0, 1, 1, 0));
+
+		// The manufacturer's deposit is the caller's tip.
+		assert_eq!(Balances::free_balance(3), 1_000 + FinalizeDeposit::get());
+		assert_eq!(Balances::free_balance(1), 1_000 - 2 * FinalizeDeposit::get());
+		assert_eq!(Balances::reserved_balance(1), FinalizeDeposit::get());
+		assert_eq!(Balances::free_balance(2), 1_000);
+		assert_eq!(TemplateModule::finalizedeposit(expired), None);
+		assert_ok!(TemplateModule::do_try_state(1));
+	})
+}
+
+#[test]
+fn finalize_expired_fail() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_noop!(TemplateModule::finalize_expired(RuntimeOrigin::root(), vec![hash]),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(TemplateModule::finalize_expired(RuntimeOrigin::signed(3), vec![hash]),
+			Error::<Test>::NothingToFinalize
+		);
+		let too_many = vec![hash; crate::MAX_FINALIZE_BATCH as usize + 1];
+		assert_noop!(TemplateModule::finalize_expired(RuntimeOrigin::signed(3), too_many),
+			Error::<Test>::TooManyProducts
+		);
+	})
+}
+
+#[test]
+fn refund_returns_the_finalize_deposit() {
+	new_test_ext().execute_with(|| {
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), 1_000);
+		assert_eq!(TemplateModule::finalizedeposit(hash), None);
+	})
+}
+
+#[test]
+fn owner_registers_warranty_of_unfinalized_sale() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let hash = HashType::from(Hashing::hash_of(&42));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), hash));
+		assert_ok!(TemplateModule::set_product_category(RuntimeOrigin::signed(1), hash, 7));
+		assert_ok!(TemplateModule::set_warranty_period(RuntimeOrigin::root(), 7, 100));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), hash));
+
+		// The window closes without anyone finalizing the sale, so registering does it.
+		Timestamp::set_timestamp(ReturnPeriod::get());
+		assert_ok!(TemplateModule::register_warranty(RuntimeOrigin::signed(2), hash));
+		System::assert_has_event(crate::Event::SaleFinalized { hash }.into());
+		assert!(TemplateModule::selledproducts(hash).is_some());
+
+		// The owner did the finalizing, so the tip is theirs.
+		assert_eq!(Balances::free_balance(2), 1_000 + FinalizeDeposit::get());
+		assert_eq!(Balances::reserved_balance(1), 0);
+	})
+}
+
+#[test]
+fn migrate_to_v4_moves_windows_to_wall_clock_time() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let pending = HashType::from(Hashing::hash_of(&1));
+		let covered = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), pending));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), covered));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), pending));
+
+		// Version 3 storage counted windows and warranties in blocks.
+		System::set_block_number(10);
+		Timestamp::set_timestamp(600_000);
+		StorageVersion::new(3).put::<TemplateModule>();
+		let _ = crate::PartialSellProduct::<Test>::clear(u32::MAX, None);
+		v3::PartialSellProduct::<Test>::insert(pending, 110);
+		v3::PartialProductTime::<Test>::insert(110, pending, ());
+		v3::ReturnPolicy::<Test>::put(50);
+		v3::WarrantyPeriod::<Test>::insert(7, 1_000);
+		v3::Warranties::<Test>::insert(
+			covered,
+			crate::Warranty { owner: 3, expires: 4, status: WarrantyStatus::Active, claim_reason: None },
+		);
+
+		MigrateToV4::<Test>::on_runtime_upgrade();
+
+		// Blocks are 6s apart, measured from block 10 at 600s.
+		assert_eq!(TemplateModule::on_chain_storage_version(), 4);
+		assert_eq!(TemplateModule::partialsellproduct(pending), Some(1_200_000));
+		assert!(v4::PartialProductTime::<Test>::contains_key(20, pending));
+		assert_eq!(v4::NextExpiryBucket::<Test>::get(), Some(20));
+		assert_eq!(TemplateModule::return_period(), 300_000);
+		assert_eq!(TemplateModule::warrantyperiod(7), Some(6_000_000));
+		assert_eq!(TemplateModule::warranties(covered).unwrap().expires, 564_000);
+	})
+}
+
+#[test]
+fn migrate_to_v5_drops_expiry_buckets() {
+	new_test_ext().execute_with(|| {
+		const TEST_ACCOUNT: <Test as frame_system::Config>::AccountId = 1;
+
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), TEST_ACCOUNT));
+		let pending = HashType::from(Hashing::hash_of(&1));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), pending));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(2), pending));
+
+		// Version 4 storage indexed return windows by expiry bucket for the hook to close them.
+		StorageVersion::new(4).put::<TemplateModule>();
+		v4::PartialProductTime::<Test>::insert(60, pending, ());
+		v4::NextExpiryBucket::<Test>::put(60);
+
+		MigrateToV5::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 5);
+		assert!(!v4::PartialProductTime::<Test>::contains_key(60, pending));
+		assert_eq!(v4::NextExpiryBucket::<Test>::get(), None);
+		assert_eq!(TemplateModule::partialsellproduct(pending), Some(ReturnPeriod::get()));
+		assert_ok!(TemplateModule::do_try_state(1));
+	})
+}
+
+#[test]
+fn migrate_to_v6_bounds_storage() {
+	new_test_ext().execute_with(|| {
+		use frame_support::storage::unhashed;
+
+		let hash = HashType::from(Hashing::hash_of(&42));
+		returned_product(hash);
+		assert_ok!(TemplateModule::open_dispute(RuntimeOrigin::signed(1), hash, b"cid".to_vec()));
+		let deadline = 1 + ArbitrationPeriod::get();
+
+		// Version 5 storage kept plain `Vec`s, which encode like the `BoundedVec`s replacing them.
+		StorageVersion::new(5).put::<TemplateModule>();
+		unhashed::put(&crate::Manufacturer::<Test>::hashed_key(), &vec![1u64, 2, 3, 4]);
+		let stored = TemplateModule::disputes(hash).unwrap();
+		let mut dispute: crate::Dispute<u64, u64, u64> = crate::Dispute {
+			opener: stored.opener,
+			customer: stored.customer,
+			manufacturer: stored.manufacturer,
+			evidence: vec![7; MaxCidLength::get() as usize + 8],
+			deposit: stored.deposit,
+			deadline: stored.deadline,
+		};
+		unhashed::put(&crate::Disputes::<Test>::hashed_key_for(hash), &dispute);
+		unhashed::put(&crate::CatalogueEndpoint::<Test>::hashed_key_for(1), &vec![0u8; 40]);
+		unhashed::put(&crate::CatalogueEndpoint::<Test>::hashed_key_for(2), &b"http://localhost".to_vec());
+
+		MigrateToV6::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 6);
+		assert_eq!(TemplateModule::manufacturers(), vec![1, 2, 3]);
+		dispute.evidence.truncate(MaxCidLength::get() as usize);
+		assert_eq!(TemplateModule::disputes(hash).unwrap().evidence, dispute.evidence);
+		assert_eq!(TemplateModule::disputedeadlines(deadline), vec![hash]);
+		assert_eq!(TemplateModule::catalogueendpoint(1), None);
+		assert_eq!(TemplateModule::catalogueendpoint(2).unwrap(), b"http://localhost".to_vec());
+	})
+}
+
+#[test]
+fn migrate_to_v6_moves_disputes_over_the_bound_to_later_blocks() {
+	new_test_ext().execute_with(|| {
+		use frame_support::storage::unhashed;
+
+		let [a, b, c] = [1u32, 2, 3].map(|i| HashType::from(Hashing::hash_of(&i)));
+		let dispute = |deadline: u64| crate::Dispute::<u64, u64, u64> {
+			opener: 2,
+			customer: 2,
+			manufacturer: 1,
+			evidence: b"cid".to_vec(),
+			deposit: 0,
+			deadline,
+		};
+		// Block 20 has one dispute more than `MaxDisputesPerBlock` and block 21 is full already.
+		StorageVersion::new(5).put::<TemplateModule>();
+		unhashed::put(&crate::DisputeDeadlines::<Test>::hashed_key_for(20), &vec![a, b]);
+		unhashed::put(&crate::DisputeDeadlines::<Test>::hashed_key_for(21), &vec![c]);
+		for (hash, deadline) in [(a, 20), (b, 20), (c, 21)] {
+			unhashed::put(&crate::Disputes::<Test>::hashed_key_for(hash), &dispute(deadline));
+		}
+
+		MigrateToV6::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::disputedeadlines(20), vec![a]);
+		assert_eq!(TemplateModule::disputedeadlines(21), vec![c]);
+		assert_eq!(TemplateModule::disputedeadlines(22), vec![b]);
+		assert_eq!(TemplateModule::disputes(a).unwrap().deadline, 20);
+		assert_eq!(TemplateModule::disputes(b).unwrap().deadline, 22);
+		assert_eq!(TemplateModule::disputes(c).unwrap().deadline, 21);
+		// Every dispute is resolved at the deadline it tells its parties, and only there.
+		for (hash, dispute) in crate::Disputes::<Test>::iter() {
+			assert_eq!(TemplateModule::disputedeadlines(dispute.deadline), vec![hash]);
+		}
+		for (block, hashes) in crate::DisputeDeadlines::<Test>::iter() {
+			assert!(hashes.iter().all(|hash| TemplateModule::disputes(hash).unwrap().deadline == block));
+		}
+	})
+}
+
+#[test]
+fn migrate_to_v7_indexes_registration_blocks_by_product() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::add_manufacturer(RuntimeOrigin::root(), 1));
+		let registered = HashType::from(Hashing::hash_of(&1));
+		let sold = HashType::from(Hashing::hash_of(&2));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), registered));
+		assert_ok!(TemplateModule::add_product(RuntimeOrigin::signed(1), sold));
+		assert_ok!(TemplateModule::check_authenticity(RuntimeOrigin::signed(3), sold));
+
+		// Version 6 storage had no reverse index, and `MigrateToV2` indexed both products at its
+		// upgrade block.
+		StorageVersion::new(6).put::<TemplateModule>();
+		let _ = RegistrationBlock::<Test>::clear(u32::MAX, None);
+		for hash in [registered, sold] {
+			RegisteredAt::<Test>::remove(1, hash);
+			RegisteredAt::<Test>::insert(4, hash, ());
+		}
+
+		MigrateToV7::<Test>::on_runtime_upgrade();
+
+		assert_eq!(TemplateModule::on_chain_storage_version(), 7);
+		// The product for sale goes back to the block it was registered at, the sold one stays.
+		assert_eq!(TemplateModule::registrationblock(registered), Some(1));
+		assert!(TemplateModule::registeredat(1, registered).is_some());
+		assert!(TemplateModule::registeredat(4, registered).is_none());
+		assert_eq!(TemplateModule::registrationblock(sold), Some(4));
+		assert!(TemplateModule::registeredat(4, sold).is_some());
+		assert_ok!(TemplateModule::do_try_state(1));
+	})
+}
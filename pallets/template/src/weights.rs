@@ -0,0 +1,286 @@
+
+//! Weights for pallet_template
+//!
+//! Placeholder weights, estimated by hand from the storage each call reads and writes. No
+//! benchmark has been run for them yet. Replace this file with measured weights on reference
+//! hardware with:
+//!
+//! ./target/release/node-template benchmark pallet --chain dev --pallet pallet_template
+//!   --extrinsic '*' --steps=50 --repeat=20 --execution=wasm --wasm-execution=compiled
+//!   --output pallets/template/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_template.
+pub trait WeightInfo {
+	fn add_manufacturer(m: u32, ) -> Weight;
+	fn add_product() -> Weight;
+	fn check_authenticity() -> Weight;
+	fn refund_products() -> Weight;
+	fn set_catalogue_endpoint() -> Weight;
+	fn open_dispute() -> Weight;
+	fn set_return_period() -> Weight;
+	fn resolve_dispute() -> Weight;
+	fn record_verification() -> Weight;
+	fn fund_sponsorship() -> Weight;
+	fn withdraw_sponsorship() -> Weight;
+	fn set_warranty_period() -> Weight;
+	fn set_product_category() -> Weight;
+	fn register_warranty() -> Weight;
+	fn file_warranty_claim() -> Weight;
+	fn accept_warranty_claim() -> Weight;
+	fn reject_warranty_claim() -> Weight;
+	fn finalize_expired(n: u32, ) -> Weight;
+	fn query_product_status() -> Weight;
+	fn product_status_response() -> Weight;
+	fn request_product_status() -> Weight;
+	fn on_initialize(d: u32, ) -> Weight;
+}
+
+/// Weights for pallet_template using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn add_manufacturer(m: u32, ) -> Weight {
+		Weight::from_parts(12_431_000, 32497)
+			.saturating_add(Weight::from_parts(52_377, 0).saturating_mul(m.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn add_product() -> Weight {
+		Weight::from_parts(24_000_000, 40122)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	fn check_authenticity() -> Weight {
+		Weight::from_parts(36_000_000, 11234)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	fn refund_products() -> Weight {
+		Weight::from_parts(33_000_000, 15861)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	fn set_catalogue_endpoint() -> Weight {
+		Weight::from_parts(15_000_000, 32497)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn open_dispute() -> Weight {
+		Weight::from_parts(35_000_000, 18706)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	fn set_return_period() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn resolve_dispute() -> Weight {
+		Weight::from_parts(51_000_000, 21297)
+			.saturating_add(T::DbWeight::get().reads(7_u64))
+			.saturating_add(T::DbWeight::get().writes(13_u64))
+	}
+	fn record_verification() -> Weight {
+		Weight::from_parts(26_000_000, 16196)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	fn fund_sponsorship() -> Weight {
+		Weight::from_parts(40_000_000, 37703)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	fn withdraw_sponsorship() -> Weight {
+		Weight::from_parts(38_000_000, 5206)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	fn set_warranty_period() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn set_product_category() -> Weight {
+		Weight::from_parts(16_000_000, 2555)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn register_warranty() -> Weight {
+		Weight::from_parts(55_000_000, 29242)
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+	}
+	fn file_warranty_claim() -> Weight {
+		Weight::from_parts(20_000_000, 3203)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn accept_warranty_claim() -> Weight {
+		Weight::from_parts(18_000_000, 5255)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn reject_warranty_claim() -> Weight {
+		Weight::from_parts(18_000_000, 5255)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn finalize_expired(n: u32, ) -> Weight {
+		Weight::from_parts(14_285_000, 5709)
+			.saturating_add(Weight::from_parts(20_412_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((4_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 10200).saturating_mul(n.into()))
+	}
+	fn query_product_status() -> Weight {
+		Weight::from_parts(30_000_000, 19282)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	fn product_status_response() -> Weight {
+		Weight::from_parts(14_000_000, 2779)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	fn request_product_status() -> Weight {
+		Weight::from_parts(28_000_000, 17290)
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
+	}
+	fn on_initialize(d: u32, ) -> Weight {
+		Weight::from_parts(4_512_000, 8300)
+			.saturating_add(Weight::from_parts(40_506_118, 0).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((4_u64).saturating_mul(d.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().writes((10_u64).saturating_mul(d.into())))
+			.saturating_add(Weight::from_parts(0, 10394).saturating_mul(d.into()))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn add_manufacturer(m: u32, ) -> Weight {
+		Weight::from_parts(12_431_000, 32497)
+			.saturating_add(Weight::from_parts(52_377, 0).saturating_mul(m.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn add_product() -> Weight {
+		Weight::from_parts(24_000_000, 40122)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	fn check_authenticity() -> Weight {
+		Weight::from_parts(36_000_000, 11234)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	fn refund_products() -> Weight {
+		Weight::from_parts(33_000_000, 15861)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	fn set_catalogue_endpoint() -> Weight {
+		Weight::from_parts(15_000_000, 32497)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn open_dispute() -> Weight {
+		Weight::from_parts(35_000_000, 18706)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn set_return_period() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn resolve_dispute() -> Weight {
+		Weight::from_parts(51_000_000, 21297)
+			.saturating_add(RocksDbWeight::get().reads(7_u64))
+			.saturating_add(RocksDbWeight::get().writes(13_u64))
+	}
+	fn record_verification() -> Weight {
+		Weight::from_parts(26_000_000, 16196)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	fn fund_sponsorship() -> Weight {
+		Weight::from_parts(40_000_000, 37703)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn withdraw_sponsorship() -> Weight {
+		Weight::from_parts(38_000_000, 5206)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn set_warranty_period() -> Weight {
+		Weight::from_parts(8_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn set_product_category() -> Weight {
+		Weight::from_parts(16_000_000, 2555)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn register_warranty() -> Weight {
+		Weight::from_parts(55_000_000, 29242)
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+	}
+	fn file_warranty_claim() -> Weight {
+		Weight::from_parts(20_000_000, 3203)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn accept_warranty_claim() -> Weight {
+		Weight::from_parts(18_000_000, 5255)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn reject_warranty_claim() -> Weight {
+		Weight::from_parts(18_000_000, 5255)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn finalize_expired(n: u32, ) -> Weight {
+		Weight::from_parts(14_285_000, 5709)
+			.saturating_add(Weight::from_parts(20_412_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((4_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((4_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 10200).saturating_mul(n.into()))
+	}
+	fn query_product_status() -> Weight {
+		Weight::from_parts(30_000_000, 19282)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	fn product_status_response() -> Weight {
+		Weight::from_parts(14_000_000, 2779)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	fn request_product_status() -> Weight {
+		Weight::from_parts(28_000_000, 17290)
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
+	}
+	fn on_initialize(d: u32, ) -> Weight {
+		Weight::from_parts(4_512_000, 8300)
+			.saturating_add(Weight::from_parts(40_506_118, 0).saturating_mul(d.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((4_u64).saturating_mul(d.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().writes((10_u64).saturating_mul(d.into())))
+			.saturating_add(Weight::from_parts(0, 10394).saturating_mul(d.into()))
+	}
+}
@@ -0,0 +1,114 @@
+//! XCM simulator network: a relay chain with a marketplace parachain querying an authenticity
+//! parachain, both running the template pallet.
+
+pub mod parachain;
+pub mod relay_chain;
+
+use frame_support::traits::GenesisBuild;
+use polkadot_parachain::primitives::Sibling;
+use sp_core::H256;
+use sp_runtime::{
+	traits::{AccountIdConversion, BlakeTwo256, Hash},
+	AccountId32,
+};
+use xcm_simulator::{decl_test_network, decl_test_parachain, decl_test_relay_chain};
+
+pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
+pub const INITIAL_BALANCE: u128 = 1_000_000_000;
+
+/// Para id of the chain that asks for product statuses.
+pub const MARKETPLACE: u32 = 1;
+/// Para id of the chain that registers products and answers.
+pub const AUTHENTICITY: u32 = 2;
+
+decl_test_parachain! {
+	pub struct ParaA {
+		Runtime = parachain::Runtime,
+		XcmpMessageHandler = parachain::MsgQueue,
+		DmpMessageHandler = parachain::MsgQueue,
+		new_ext = para_ext(MARKETPLACE),
+	}
+}
+
+decl_test_parachain! {
+	pub struct ParaB {
+		Runtime = parachain::Runtime,
+		XcmpMessageHandler = parachain::MsgQueue,
+		DmpMessageHandler = parachain::MsgQueue,
+		new_ext = para_ext(AUTHENTICITY),
+	}
+}
+
+decl_test_relay_chain! {
+	pub struct Relay {
+		Runtime = relay_chain::Runtime,
+		XcmConfig = relay_chain::XcmConfig,
+		new_ext = relay_ext(),
+	}
+}
+
+decl_test_network! {
+	pub struct MockNet {
+		relay_chain = Relay,
+		parachains = vec![
+			(MARKETPLACE, ParaA),
+			(AUTHENTICITY, ParaB),
+		],
+	}
+}
+
+/// Sovereign account of sibling `para` on a parachain.
+pub fn sibling_account_id(para: u32) -> AccountId32 {
+	Sibling::from(para).into_account_truncating()
+}
+
+/// The product Alice registered on the authenticity chain.
+pub fn product_hash() -> H256 {
+	BlakeTwo256::hash(b"Alice/product/0")
+}
+
+pub fn para_ext(para_id: u32) -> sp_io::TestExternalities {
+	use parachain::{MsgQueue, Runtime, System};
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![
+			(ALICE, INITIAL_BALANCE),
+			(sibling_account_id(MARKETPLACE), INITIAL_BALANCE),
+			(sibling_account_id(AUTHENTICITY), INITIAL_BALANCE),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	if para_id == AUTHENTICITY {
+		crate::GenesisConfig::<Runtime> {
+			manufacturers: vec![(ALICE, b"Alice Manufacturing".to_vec())],
+			products: vec![(ALICE, product_hash())],
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+	}
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		MsgQueue::set_para_id(para_id.into());
+	});
+	ext
+}
+
+pub fn relay_ext() -> sp_io::TestExternalities {
+	use relay_chain::{Runtime, System};
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+
+	pallet_balances::GenesisConfig::<Runtime> { balances: vec![(ALICE, INITIAL_BALANCE)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
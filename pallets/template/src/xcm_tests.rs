@@ -0,0 +1,285 @@
+use crate::{
+	xcm_mock::{parachain::*, *},
+	Error, Event as TemplateEvent, ProductStatus, ResponseCall,
+};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, weights::Weight};
+use sp_core::H256;
+use sp_runtime::DispatchError;
+use xcm::latest::prelude::*;
+use xcm_simulator::TestExt;
+
+// `product_status_response` of the marketplace's template pallet, where answers go.
+fn response_call() -> ResponseCall {
+	let call = RuntimeCall::TemplateModule(crate::Call::product_status_response {
+		query_id: 0,
+		hash: H256::zero(),
+		status: None,
+	})
+	.encode();
+	ResponseCall {
+		pallet_index: call[0],
+		call_index: call[1],
+		max_weight: MaxResponseWeight::get(),
+	}
+}
+
+// `query_product_status` of the authenticity chain's template pallet.
+fn query_call() -> ResponseCall {
+	let call = RuntimeCall::TemplateModule(crate::Call::query_product_status {
+		query_id: 0,
+		hash: H256::zero(),
+		response: response_call(),
+	})
+	.encode();
+	ResponseCall {
+		pallet_index: call[0],
+		call_index: call[1],
+		max_weight: MaxResponseWeight::get(),
+	}
+}
+
+fn authenticity() -> MultiLocation {
+	(Parent, Parachain(AUTHENTICITY)).into()
+}
+
+// A status query of `hash`, paid for from the marketplace's sovereign account.
+fn status_query(query_id: u64, hash: H256) -> Xcm<()> {
+	let call = RuntimeCall::TemplateModule(crate::Call::query_product_status {
+		query_id,
+		hash,
+		response: response_call(),
+	});
+	let fees: MultiAsset = (Parent, 1_000).into();
+	Xcm(vec![
+		WithdrawAsset(fees.clone().into()),
+		BuyExecution { fees, weight_limit: Unlimited },
+		Transact {
+			origin_kind: OriginKind::Xcm,
+			require_weight_at_most: MaxResponseWeight::get(),
+			call: call.encode().into(),
+		},
+	])
+}
+
+#[test]
+fn status_query_round_trip() {
+	MockNet::reset();
+
+	ParaA::execute_with(|| {
+		assert_ok!(TemplateModule::request_product_status(
+			RuntimeOrigin::root(),
+			Box::new(authenticity()),
+			product_hash(),
+			query_call(),
+		));
+		assert_eq!(TemplateModule::statusqueries(0), Some((authenticity(), product_hash())));
+	});
+
+	ParaB::execute_with(|| {
+		System::assert_has_event(
+			TemplateEvent::ProductStatusQueried {
+				querier: (Parent, Parachain(MARKETPLACE)).into(),
+				query_id: 0,
+				hash: product_hash(),
+				status: Some(ProductStatus::Registered),
+			}
+			.into(),
+		);
+	});
+
+	ParaA::execute_with(|| {
+		System::assert_has_event(
+			TemplateEvent::ProductStatusReceived {
+				responder: authenticity(),
+				query_id: 0,
+				hash: product_hash(),
+				status: Some(ProductStatus::Registered),
+			}
+			.into(),
+		);
+		// The query is answered, another answer to it would be rejected.
+		assert_eq!(TemplateModule::statusqueries(0), None);
+	});
+}
+
+#[test]
+fn unknown_product_is_answered_with_no_status() {
+	MockNet::reset();
+	let unknown = H256::repeat_byte(9);
+
+	ParaA::execute_with(|| {
+		assert_ok!(TemplateModule::request_product_status(
+			RuntimeOrigin::root(),
+			Box::new(authenticity()),
+			unknown,
+			query_call(),
+		));
+	});
+
+	ParaA::execute_with(|| {
+		System::assert_has_event(
+			TemplateEvent::ProductStatusReceived {
+				responder: authenticity(),
+				query_id: 0,
+				hash: unknown,
+				status: None,
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn paid_status_query_is_answered() {
+	MockNet::reset();
+
+	ParaA::execute_with(|| {
+		// Sent by hand rather than with `request_product_status`, so record it the same way.
+		crate::StatusQueries::<Runtime>::insert(7, (authenticity(), product_hash()));
+		assert_ok!(PolkadotXcm::send_xcm(Here, authenticity(), status_query(7, product_hash())));
+	});
+
+	ParaA::execute_with(|| {
+		System::assert_has_event(
+			TemplateEvent::ProductStatusReceived {
+				responder: authenticity(),
+				query_id: 7,
+				hash: product_hash(),
+				status: Some(ProductStatus::Registered),
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn only_answers_to_outstanding_queries_are_taken() {
+	MockNet::reset();
+
+	ParaA::execute_with(|| {
+		let answer = |from: u32, query_id: u64, hash: H256| {
+			let responder = RuntimeOrigin::from(pallet_xcm::Origin::Xcm((Parent, Parachain(from)).into()));
+			TemplateModule::product_status_response(responder, query_id, hash, None)
+		};
+
+		// Nothing was asked.
+		assert_noop!(answer(AUTHENTICITY, 3, product_hash()), Error::<Runtime>::UnknownQuery);
+
+		crate::StatusQueries::<Runtime>::insert(3, (authenticity(), product_hash()));
+		// Another chain than the one asked, or another product than the one asked about.
+		assert_noop!(answer(3, 3, product_hash()), Error::<Runtime>::UnexpectedResponse);
+		assert_noop!(answer(AUTHENTICITY, 3, H256::repeat_byte(9)), Error::<Runtime>::UnexpectedResponse);
+
+		assert_ok!(answer(AUTHENTICITY, 3, product_hash()));
+		assert_noop!(answer(AUTHENTICITY, 3, product_hash()), Error::<Runtime>::UnknownQuery);
+	});
+}
+
+#[test]
+fn status_request_needs_root() {
+	MockNet::reset();
+
+	ParaA::execute_with(|| {
+		assert_noop!(
+			TemplateModule::request_product_status(
+				RuntimeOrigin::signed(ALICE),
+				Box::new(authenticity()),
+				product_hash(),
+				query_call(),
+			),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn status_request_fails_if_it_cannot_be_routed() {
+	MockNet::reset();
+
+	ParaA::execute_with(|| {
+		assert_noop!(
+			TemplateModule::request_product_status(
+				RuntimeOrigin::root(),
+				Box::new((Parent, Parachain(9999)).into()),
+				product_hash(),
+				query_call(),
+			),
+			Error::<Runtime>::QueryNotSent
+		);
+	});
+}
+
+#[test]
+fn status_query_needs_an_xcm_origin() {
+	MockNet::reset();
+
+	ParaB::execute_with(|| {
+		assert_noop!(
+			TemplateModule::query_product_status(
+				RuntimeOrigin::signed(ALICE),
+				7,
+				product_hash(),
+				response_call(),
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			TemplateModule::product_status_response(RuntimeOrigin::signed(ALICE), 7, product_hash(), None),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn status_query_fails_if_the_answer_cannot_be_routed() {
+	MockNet::reset();
+
+	ParaB::execute_with(|| {
+		// A parachain missing from the network, which the router has no way to reach.
+		let querier =
+			RuntimeOrigin::from(pallet_xcm::Origin::Xcm((Parent, Parachain(9999)).into()));
+		assert_noop!(
+			TemplateModule::query_product_status(querier, 7, product_hash(), response_call()),
+			Error::<Runtime>::ResponseNotSent
+		);
+	});
+}
+
+#[test]
+fn status_query_only_comes_from_sibling_parachains() {
+	MockNet::reset();
+
+	ParaB::execute_with(|| {
+		let relay_account = RuntimeOrigin::from(pallet_xcm::Origin::Xcm(
+			(Parent, AccountId32 { network: None, id: [7; 32] }).into(),
+		));
+		assert_noop!(
+			TemplateModule::query_product_status(relay_account, 7, product_hash(), response_call()),
+			DispatchError::BadOrigin
+		);
+		let relay = RuntimeOrigin::from(pallet_xcm::Origin::Xcm(Parent.into()));
+		assert_noop!(
+			TemplateModule::query_product_status(relay, 7, product_hash(), response_call()),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn status_query_answer_weight_is_capped() {
+	MockNet::reset();
+
+	ParaB::execute_with(|| {
+		let querier =
+			RuntimeOrigin::from(pallet_xcm::Origin::Xcm((Parent, Parachain(MARKETPLACE)).into()));
+		let response = ResponseCall {
+			max_weight: MaxResponseWeight::get().saturating_add(Weight::from_parts(1, 0)),
+			..response_call()
+		};
+		assert_noop!(
+			TemplateModule::query_product_status(querier, 7, product_hash(), response),
+			Error::<Runtime>::ResponseTooHeavy
+		);
+	});
+}
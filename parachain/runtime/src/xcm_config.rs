@@ -0,0 +1,276 @@
+//! How the parachain talks XCM to the relay chain and its siblings.
+
+use super::{
+	AccountId, AllPalletsWithSystem, Balances, ParachainInfo, ParachainSystem, PolkadotXcm,
+	Runtime, RuntimeCall, RuntimeEvent, RuntimeOrigin, WeightToFee, XcmpQueue,
+};
+use codec::{Decode, Encode};
+use core::marker::PhantomData;
+use frame_support::{
+	match_types, parameter_types,
+	traits::{ConstU32, Contains, Everything, Nothing},
+	weights::Weight,
+};
+use pallet_xcm::XcmPassthrough;
+use polkadot_parachain::primitives::Sibling;
+use polkadot_runtime_common::impls::ToAuthor;
+use xcm::{latest::prelude::*, DoubleEncoded};
+use xcm_builder::{
+	AccountId32Aliases, AllowExplicitUnpaidExecutionFrom, AllowTopLevelPaidExecutionFrom,
+	CurrencyAdapter, EnsureXcmOrigin, FixedWeightBounds, IsConcrete, NativeAsset, ParentIsPreset,
+	RelayChainAsNative, SiblingParachainAsNative, SiblingParachainConvertsVia,
+	SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation, TakeWeightCredit,
+	UsingComponents, WithComputedOrigin,
+};
+use xcm_executor::{traits::ShouldExecute, XcmExecutor};
+
+parameter_types! {
+	pub const RelayLocation: MultiLocation = MultiLocation::parent();
+	pub const RelayNetwork: Option<NetworkId> = None;
+	pub RelayChainOrigin: RuntimeOrigin = cumulus_pallet_xcm::Origin::Relay.into();
+	pub UniversalLocation: InteriorMultiLocation = Parachain(ParachainInfo::parachain_id().into()).into();
+}
+
+/// Converts a `MultiLocation` into an `AccountId`, for the sovereign accounts of other chains
+/// and for the dispatch origin of `Transact`.
+pub type LocationToAccountId = (
+	// The relay chain converts to the parent `AccountId`.
+	ParentIsPreset<AccountId>,
+	// Sibling parachains convert to their sovereign account.
+	SiblingParachainConvertsVia<Sibling, AccountId>,
+	// `AccountId32` locations alias the local account of the same 32 bytes.
+	AccountId32Aliases<RelayNetwork, AccountId>,
+);
+
+/// Transacts the relay chain token, which the parachain holds in `Balances`.
+pub type LocalAssetTransactor = CurrencyAdapter<
+	Balances,
+	IsConcrete<RelayLocation>,
+	LocationToAccountId,
+	AccountId,
+	// No teleports are tracked.
+	(),
+>;
+
+/// Converts an incoming XCM origin into the `RuntimeOrigin` a `Transact` is dispatched with.
+pub type XcmOriginToTransactDispatchOrigin = (
+	// A signed origin of the location's sovereign account.
+	SovereignSignedViaLocation<LocationToAccountId, RuntimeOrigin>,
+	// The relay chain as the `Relay` origin.
+	RelayChainAsNative<RelayChainOrigin, RuntimeOrigin>,
+	// Sibling parachains as the `SiblingParachain` origin.
+	SiblingParachainAsNative<cumulus_pallet_xcm::Origin, RuntimeOrigin>,
+	// `AccountId32` locations as the signed origin of the same account.
+	SignedAccountId32AsNative<RelayNetwork, RuntimeOrigin>,
+	// Anything else as the XCM pallet's `Xcm` origin.
+	XcmPassthrough<RuntimeOrigin>,
+);
+
+parameter_types! {
+	// One XCM operation is 1_000_000_000 weight, almost certainly a conservative estimate.
+	pub UnitWeightCost: Weight = Weight::from_parts(1_000_000_000, 64 * 1024);
+	pub const MaxInstructions: u32 = 100;
+	pub const MaxAssetsIntoHolding: u32 = 64;
+}
+
+match_types! {
+	pub type ParentOrParentsExecutivePlurality: impl Contains<MultiLocation> = {
+		MultiLocation { parents: 1, interior: Here } |
+		MultiLocation { parents: 1, interior: X1(Plurality { id: BodyId::Executive, .. }) }
+	};
+	pub type SiblingParachains: impl Contains<MultiLocation> = {
+		MultiLocation { parents: 1, interior: X1(Parachain(_)) }
+	};
+}
+
+/// Lets `T` ask for and answer product status queries without paying, as an `UnpaidExecution`
+/// followed by a single `Transact` of `query_product_status` or `product_status_response`.
+/// Anything else they send has to pay.
+pub struct AllowUnpaidStatusQueriesFrom<T>(PhantomData<T>);
+impl<T: Contains<MultiLocation>> ShouldExecute for AllowUnpaidStatusQueriesFrom<T> {
+	fn should_execute<Call>(
+		origin: &MultiLocation,
+		instructions: &mut [Instruction<Call>],
+		max_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		if !T::contains(origin) {
+			return Err(())
+		}
+		let call = match instructions {
+			[UnpaidExecution { weight_limit: Limited(limit), .. }, Transact { call, .. }]
+				if limit.all_gte(max_weight) =>
+				call,
+			[UnpaidExecution { weight_limit: Unlimited, .. }, Transact { call, .. }] => call,
+			_ => return Err(()),
+		};
+		// The barrier is generic over the call type, so decode the call as this runtime's.
+		let call = DoubleEncoded::<RuntimeCall>::decode(&mut &call.encode()[..]).map_err(|_| ())?;
+		match call.try_into()? {
+			RuntimeCall::TemplateModule(
+				pallet_template::Call::query_product_status { .. } |
+				pallet_template::Call::product_status_response { .. },
+			) => Ok(()),
+			_ => Err(()),
+		}
+	}
+}
+
+pub type Barrier = (
+	TakeWeightCredit,
+	WithComputedOrigin<
+		(
+			AllowTopLevelPaidExecutionFrom<Everything>,
+			// The relay chain and its executive plurality execute for free.
+			AllowExplicitUnpaidExecutionFrom<ParentOrParentsExecutivePlurality>,
+			// Sibling parachains query product statuses and answer them for free.
+			AllowUnpaidStatusQueriesFrom<SiblingParachains>,
+		),
+		UniversalLocation,
+		ConstU32<8>,
+	>,
+);
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type RuntimeCall = RuntimeCall;
+	type XcmSender = XcmRouter;
+	type AssetTransactor = LocalAssetTransactor;
+	type OriginConverter = XcmOriginToTransactDispatchOrigin;
+	type IsReserve = NativeAsset;
+	// Teleporting is disabled.
+	type IsTeleporter = ();
+	type UniversalLocation = UniversalLocation;
+	type Barrier = Barrier;
+	type Weigher = FixedWeightBounds<UnitWeightCost, RuntimeCall, MaxInstructions>;
+	type Trader =
+		UsingComponents<WeightToFee, RelayLocation, AccountId, Balances, ToAuthor<Runtime>>;
+	type ResponseHandler = PolkadotXcm;
+	type AssetTrap = PolkadotXcm;
+	type AssetClaims = PolkadotXcm;
+	type SubscriptionService = PolkadotXcm;
+	type PalletInstancesInfo = AllPalletsWithSystem;
+	type MaxAssetsIntoHolding = MaxAssetsIntoHolding;
+	type AssetLocker = ();
+	type AssetExchanger = ();
+	type FeeManager = ();
+	type MessageExporter = ();
+	type UniversalAliases = Nothing;
+	type CallDispatcher = RuntimeCall;
+	type SafeCallFilter = Everything;
+}
+
+/// Local signed origins send XCM as their `AccountId32` location.
+pub type LocalOriginToLocation = SignedToAccountId32<RuntimeOrigin, AccountId, RelayNetwork>;
+
+/// Routes outgoing XCM: UMP to the relay chain, XCMP to the siblings.
+pub type XcmRouter = (
+	cumulus_primitives_utility::ParentAsUmp<ParachainSystem, PolkadotXcm, ()>,
+	XcmpQueue,
+);
+
+#[cfg(feature = "runtime-benchmarks")]
+parameter_types! {
+	pub ReachableDest: Option<MultiLocation> = Some(Parent.into());
+}
+
+impl pallet_xcm::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type SendXcmOrigin = EnsureXcmOrigin<RuntimeOrigin, LocalOriginToLocation>;
+	type XcmRouter = XcmRouter;
+	type ExecuteXcmOrigin = EnsureXcmOrigin<RuntimeOrigin, LocalOriginToLocation>;
+	// Local accounts can't execute XCM with `PolkadotXcm::execute`.
+	type XcmExecuteFilter = Nothing;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+	type XcmTeleportFilter = Everything;
+	type XcmReserveTransferFilter = Nothing;
+	type Weigher = FixedWeightBounds<UnitWeightCost, RuntimeCall, MaxInstructions>;
+	type UniversalLocation = UniversalLocation;
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+
+	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
+	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+	type Currency = Balances;
+	type CurrencyMatcher = ();
+	type TrustedLockers = ();
+	type SovereignAccountOf = LocationToAccountId;
+	type MaxLockers = ConstU32<8>;
+	type WeightInfo = pallet_xcm::TestWeightInfo;
+	#[cfg(feature = "runtime-benchmarks")]
+	type ReachableDest = ReachableDest;
+}
+
+impl cumulus_pallet_xcm::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn unpaid_transact(call: RuntimeCall) -> Xcm<RuntimeCall> {
+		Xcm(vec![
+			UnpaidExecution { weight_limit: Unlimited, check_origin: None },
+			Transact {
+				origin_kind: OriginKind::Xcm,
+				require_weight_at_most: Weight::from_parts(1_000_000, 64 * 1024),
+				call: call.encode().into(),
+			},
+		])
+	}
+
+	fn should_execute(origin: MultiLocation, mut message: Xcm<RuntimeCall>) -> Result<(), ()> {
+		Barrier::should_execute(
+			&origin,
+			&mut message.0,
+			Weight::from_parts(2_000_000_000, 128 * 1024),
+			&mut Weight::zero(),
+		)
+	}
+
+	fn answer() -> RuntimeCall {
+		RuntimeCall::TemplateModule(pallet_template::Call::product_status_response {
+			query_id: 0,
+			hash: Default::default(),
+			status: None,
+		})
+	}
+
+	#[test]
+	fn siblings_query_and_answer_product_statuses_unpaid() {
+		let sibling = MultiLocation::new(1, X1(Parachain(2000)));
+		assert_eq!(should_execute(sibling, unpaid_transact(answer())), Ok(()));
+
+		let query = RuntimeCall::TemplateModule(pallet_template::Call::query_product_status {
+			query_id: 0,
+			hash: Default::default(),
+			response: pallet_template::ResponseCall {
+				pallet_index: 40,
+				call_index: 0,
+				max_weight: Weight::from_parts(1_000_000, 64 * 1024),
+			},
+		});
+		assert_eq!(should_execute(sibling, unpaid_transact(query)), Ok(()));
+	}
+
+	#[test]
+	fn siblings_pay_for_other_calls() {
+		let sibling = MultiLocation::new(1, X1(Parachain(2000)));
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		assert_eq!(should_execute(sibling, unpaid_transact(remark)), Err(()));
+
+		// A single `Transact` of an answer, nothing more.
+		let mut message = unpaid_transact(answer());
+		message.0.push(ClearOrigin);
+		assert_eq!(should_execute(sibling, message), Err(()));
+	}
+
+	#[test]
+	fn only_siblings_answer_unpaid() {
+		let account =
+			MultiLocation::new(1, X2(Parachain(2000), AccountId32 { network: None, id: [7; 32] }));
+		assert_eq!(should_execute(account, unpaid_transact(answer())), Err(()));
+	}
+}
@@ -9,15 +9,17 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
+use codec::Encode;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
+		AccountIdLookup, BlakeTwo256, Block as BlockT, Extrinsic as ExtrinsicT, IdentifyAccount,
+		NumberFor, One, SaturatedConversion, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
 use sp_std::prelude::*;
@@ -29,7 +31,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		ConstU128, ConstU32, ConstU64, ConstU8, EqualPrivilegeOnly, KeyOwnerProofSystem,
+		Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -67,6 +70,9 @@ pub type Balance = u128;
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
+/// Wall-clock time in milliseconds since the Unix epoch.
+pub type Moment = u64;
+
 /// A hash of some data used by the chain.
 pub type Hash = sp_core::H256;
 
@@ -106,10 +112,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 111,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 3,
 	state_version: 1,
 };
 
@@ -130,6 +136,10 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+// Wall-clock durations, in milliseconds.
+pub const MINUTE_MS: Moment = 60_000;
+pub const DAY_MS: Moment = 24 * 60 * MINUTE_MS;
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -236,7 +246,7 @@ impl pallet_grandpa::Config for Runtime {
 
 impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
-	type Moment = u64;
+	type Moment = Moment;
 	type OnTimestampSet = Aura;
 	type MinimumPeriod = ConstU64<{ SLOT_DURATION / 2 }>;
 	type WeightInfo = ();
@@ -278,13 +288,140 @@ impl pallet_sudo::Config for Runtime {
 }
 
 parameter_types! {
-	pub const TimeDuration: u32 = 2_16_000;
+	pub const PreimageBaseDeposit: Balance = 100 * EXISTENTIAL_DEPOSIT;
+	pub const PreimageByteDeposit: Balance = EXISTENTIAL_DEPOSIT;
+}
+
+impl pallet_preimage::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type Currency = Balances;
+	type ManagerOrigin = frame_system::EnsureRoot<AccountId>;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type PalletsOrigin = OriginCaller;
+	type RuntimeCall = RuntimeCall;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = ConstU32<50>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type Preimages = Preimage;
+}
+
+parameter_types! {
+	pub const ReturnPeriod: Moment = 15 * DAY_MS;
+	pub const DisputeDeposit: Balance = 100 * EXISTENTIAL_DEPOSIT;
+	pub const FinalizeDeposit: Balance = EXISTENTIAL_DEPOSIT;
+	pub const ArbitrationPeriod: BlockNumber = 7 * DAYS;
+	pub const MaxCatalogueBatch: u32 = 100;
+	pub const CatalogueSyncInterval: BlockNumber = 10 * MINUTES;
+	// Below every signed transaction, so anonymous scans can't crowd out paying ones.
+	pub const VerificationUnsignedPriority: TransactionPriority = TransactionPriority::min_value();
+	pub const MaxVerificationsPerBlock: u32 = 100;
+	pub const TemplatePalletId: frame_support::PalletId = frame_support::PalletId(*b"tmpl/spn");
+	pub const MaxSponsoredPerProduct: u32 = 4;
+	pub const MaxSponsoredPerCustomer: u32 = 20;
+	pub const MaxPageSize: u32 = 1_000;
+	pub const MaxManufacturers: u32 = 1_000;
+	pub const MaxDisputesPerBlock: u32 = 100;
+	pub const MaxCidLength: u32 = 128;
+	pub const MaxEndpointLength: u32 = 256;
+	pub MaxResponseWeight: Weight = Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 10, 64 * 1024);
 }
 
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	type expire_time = TimeDuration;
+	type WeightInfo = pallet_template::weights::SubstrateWeight<Runtime>;
+	type ReturnPeriod = ReturnPeriod;
+	type Currency = Balances;
+	type ArbiterOrigin = frame_system::EnsureRoot<AccountId>;
+	type DisputeDeposit = DisputeDeposit;
+	type FinalizeDeposit = FinalizeDeposit;
+	type ArbitrationPeriod = ArbitrationPeriod;
+	type AuthorityId = pallet_template::crypto::CatalogueAuthId;
+	type MaxCatalogueBatch = MaxCatalogueBatch;
+	type CatalogueSyncInterval = CatalogueSyncInterval;
+	type UnsignedPriority = VerificationUnsignedPriority;
+	type MaxVerificationsPerBlock = MaxVerificationsPerBlock;
+	type PalletId = TemplatePalletId;
+	type MaxSponsoredPerProduct = MaxSponsoredPerProduct;
+	type MaxSponsoredPerCustomer = MaxSponsoredPerCustomer;
+	type MaxPageSize = MaxPageSize;
+	type MaxManufacturers = MaxManufacturers;
+	type MaxDisputesPerBlock = MaxDisputesPerBlock;
+	type MaxCidLength = MaxCidLength;
+	type MaxEndpointLength = MaxEndpointLength;
+	// The solo chain has no XCM, so it neither answers nor sends status queries.
+	type XcmOrigin = frame_support::traits::NeverEnsureOrigin<xcm::latest::MultiLocation>;
+	type XcmSender = ();
+	type MaxResponseWeight = MaxResponseWeight;
+	#[cfg(feature = "runtime-benchmarks")]
+	type ReachableQuerier = frame_support::traits::GetDefault;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Index,
+	) -> Option<(RuntimeCall, <UncheckedExtrinsic as ExtrinsicT>::SignaturePayload)> {
+		let tip = 0;
+		// take the biggest period possible.
+		let period =
+			BlockHashCount::get().checked_next_power_of_two().map(|c| c / 2).unwrap_or(2) as u64;
+		let current_block = System::block_number()
+			.saturated_into::<u64>()
+			// The `System::block_number` is initialized with `n+1`,
+			// so the actual block number is `n`.
+			.saturating_sub(1);
+		let extra: SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_template::ChargeSponsoredTransactionPayment::<Runtime>::from(tip),
+		);
+		let raw_payload = SignedPayload::new(call, extra)
+			.map_err(|e| {
+				log::warn!("Unable to create signed payload: {:?}", e);
+			})
+			.ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let address = Address::Id(account);
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (address, signature, extra)))
+	}
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = RuntimeCall;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -303,6 +440,8 @@ construct_runtime!(
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
+		Preimage: pallet_preimage,
+		Scheduler: pallet_scheduler,
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template,
 	}
@@ -323,7 +462,7 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_template::ChargeSponsoredTransactionPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -331,6 +470,16 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Storage migrations run on the next runtime upgrade.
+pub type Migrations = (
+	pallet_template::migrations::v1::MigrateToV1<Runtime>,
+	pallet_template::migrations::v2::MigrateToV2<Runtime>,
+	pallet_template::migrations::v3::MigrateToV3<Runtime>,
+	pallet_template::migrations::v4::MigrateToV4<Runtime>,
+	pallet_template::migrations::v5::MigrateToV5<Runtime>,
+	pallet_template::migrations::v6::MigrateToV6<Runtime>,
+	pallet_template::migrations::v7::MigrateToV7<Runtime>,
+);
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -338,6 +487,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -351,6 +501,8 @@ mod benches {
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
 		[pallet_timestamp, Timestamp]
+		[pallet_preimage, Preimage]
+		[pallet_scheduler, Scheduler]
 		[pallet_template, TemplateModule]
 	);
 }
@@ -516,6 +668,53 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_template_runtime_api::TemplateApi<Block, AccountId, Hash, BlockNumber, Moment> for Runtime {
+		fn product_status(hash: Hash) -> Option<pallet_template::ProductStatus> {
+			TemplateModule::product_status(&hash)
+		}
+
+		fn product_category(hash: Hash) -> pallet_template::CategoryId {
+			TemplateModule::productcategory(hash)
+		}
+
+		fn warranty_period(category: pallet_template::CategoryId) -> Option<Moment> {
+			TemplateModule::warrantyperiod(category)
+		}
+
+		fn warranty(hash: Hash) -> Option<pallet_template::Warranty<AccountId, Moment>> {
+			TemplateModule::warranties(hash).map(pallet_template::Warranty::into_unbounded)
+		}
+
+		fn products_by_status(
+			status: pallet_template::ProductStatus,
+			cursor: Option<Hash>,
+			limit: u32,
+		) -> pallet_template::ProductPage<Hash, Hash> {
+			TemplateModule::products_by_status(status, cursor, limit)
+		}
+
+		fn products_by_manufacturer(
+			manufacturer: AccountId,
+			cursor: Option<Hash>,
+			limit: u32,
+		) -> pallet_template::ProductPage<Hash, Hash> {
+			TemplateModule::products_by_manufacturer(manufacturer, cursor, limit)
+		}
+
+		fn products_registered_between(
+			from: BlockNumber,
+			to: BlockNumber,
+			cursor: Option<pallet_template::RangeCursor<BlockNumber, Hash>>,
+			limit: u32,
+		) -> pallet_template::ProductPage<Hash, pallet_template::RangeCursor<BlockNumber, Hash>> {
+			TemplateModule::products_registered_between(from, to, cursor, limit)
+		}
+
+		fn manufacturer_counts(manufacturer: AccountId) -> pallet_template::ProductCounts {
+			TemplateModule::manufacturer_counts(&manufacturer)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (